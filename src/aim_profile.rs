@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::scoring::ScoreCard;
+
+const REFERENCE_KILLS_PER_SECOND: f32 = 2.0; // Kill rate that earns the full speed half of a clicking/switching result
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Category {
+    Clicking,
+    Tracking,
+    Switching,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Skill {
+    Speed,
+    Precision,
+    Control,
+    Reactivity,
+    Evasion,
+}
+
+// A scenario result on a 0-100 scale: time on target for tracking, otherwise half kill rate and half accuracy
+pub fn normalized_score(card: &ScoreCard) -> f32 {
+    let result = if card.is_tracking() {
+        card.time_on_target()
+    } else {
+        0.5 * (card.kills_per_second() / REFERENCE_KILLS_PER_SECOND).min(1.0) + 0.5 * card.accuracy()
+    };
+    result.clamp(0.0, 1.0) * 100.0
+}
+
+// Normalized results rolled up per category and per sub-skill
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AimProfile {
+    pub categories: BTreeMap<Category, f32>,
+    pub skills: BTreeMap<Skill, f32>,
+}
+
+impl AimProfile {
+    pub fn from_cards(cards: &[ScoreCard]) -> Self {
+        let mut categories: BTreeMap<Category, (f32, f32)> = BTreeMap::new();
+        let mut skills: BTreeMap<Skill, (f32, f32)> = BTreeMap::new();
+        for card in cards {
+            let Some(scenario) = card.scenario else { continue };
+            let score = normalized_score(card);
+            let entry = categories.entry(scenario.def().category).or_default();
+            *entry = (entry.0 + score, entry.1 + 1.0);
+            // Each scenario definition weights how much it counts towards each sub-skill
+            for (&skill, &weight) in &scenario.def().skills {
+                let entry = skills.entry(skill).or_default();
+                *entry = (entry.0 + score * weight, entry.1 + weight);
+            }
+        }
+
+        let mean = |(sum, weight): (f32, f32)| sum / weight;
+        Self {
+            categories: categories.into_iter().map(|(category, total)| (category, mean(total))).collect(),
+            skills: skills.into_iter().map(|(skill, total)| (skill, mean(total))).collect(),
+        }
+    }
+
+    pub fn overall(&self) -> Option<f32> {
+        (!self.categories.is_empty()).then(|| self.categories.values().sum::<f32>() / self.categories.len() as f32)
+    }
+
+    // (label, score) for every category then every sub-skill, the order the chart shows them in
+    pub fn entries(&self) -> Vec<(String, f32)> {
+        self.categories.iter().map(|(category, &score)| (format!("{:?}", category), score))
+            .chain(self.skills.iter().map(|(skill, &score)| (format!("{:?}", skill), score)))
+            .collect()
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::{Path, PathBuf}};
+
+use crate::{history::{self, SessionRecord}, profile::{self, Profile, ProfileError}};
+
+// A profile plus optional history in one JSON file for sharing setups:
+//   { "format": "neurocurve-bundle", "version": 1, "checksum": "<fnv-1a 64 of contents>", "contents": { ... } }
+// The checksum covers the compact serialization of `contents`, so reformatting the file keeps it valid
+const FORMAT: &str = "neurocurve-bundle";
+const VERSION: u32 = 1;
+// Fields a merge takes from the bundle unless `--bundle-fields` names others: the aim setup, while the local mouse,
+// game, crosshair, keybinds and playlist stay
+const DEFAULT_MERGE_FIELDS: &[&str] = &["sensitivity_cm_per_360", "camera_fov", "fov_axis", "ads_fov", "game_fov", "sens_match", "curve"];
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    checksum: String,
+    contents: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub app_version: String,
+    pub name: String,
+    pub profile: Profile, // DPI, cm/360, FOV, curve, crosshair and keybinds
+    pub history: Option<Vec<SessionRecord>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportMode {
+    Merge { fields: Vec<String> }, // Take the named profile fields from the bundle, keep the rest of the local profile
+    KeepBoth, // Keep the local profile; a differing one of the same name is imported as `<name>-imported`
+    Replace,  // Overwrite the local profile of the same name and its history
+}
+
+#[derive(Debug)]
+pub enum BundleError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    Format(String),
+    Version(u32),
+    Checksum { expected: String, actual: String },
+    Profile(ProfileError),
+    Merge(String),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            Self::Parse(path, err) => write!(f, "{}: {}", path.display(), err),
+            Self::Format(format) => write!(f, "not a profile bundle (format {:?})", format),
+            Self::Version(version) => write!(f, "bundle version {} is newer than supported version {}", version, VERSION),
+            Self::Checksum { expected, actual } => write!(f, "checksum mismatch (expected {}, got {}), the file is damaged or was edited", expected, actual),
+            Self::Profile(err) => write!(f, "invalid profile: {}", err),
+            Self::Merge(reason) => write!(f, "can't merge profiles: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+// 64-bit FNV-1a, enough to catch truncated or hand-edited files
+fn checksum(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+impl Bundle {
+    // Bundle a profile, with no history, the history recorded under it (Some(false)) or all of it (Some(true))
+    pub fn new(profile: &Profile, history: Option<bool>) -> Self {
+        let history = history.map(|all| history::load().into_iter()
+            .filter(|record| all || record.profile_name == profile.name)
+            .collect());
+        Self { app_version: env!("CARGO_PKG_VERSION").to_string(), name: profile.name.clone(), profile: profile.clone(), history }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), BundleError> {
+        let parse_error = |err: serde_json::Error| BundleError::Parse(path.to_path_buf(), err.to_string());
+        let contents = serde_json::to_value(self).map_err(parse_error)?;
+        let compact = serde_json::to_string(&contents).map_err(parse_error)?;
+        let envelope = Envelope { format: FORMAT.to_string(), version: VERSION, checksum: checksum(compact.as_bytes()), contents };
+        let json = serde_json::to_string_pretty(&envelope).map_err(parse_error)?;
+        fs::write(path, json).map_err(|e| BundleError::Io(path.to_path_buf(), e))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, BundleError> {
+        let parse_error = |err: serde_json::Error| BundleError::Parse(path.to_path_buf(), err.to_string());
+        let json = fs::read_to_string(path).map_err(|e| BundleError::Io(path.to_path_buf(), e))?;
+        let envelope: Envelope = serde_json::from_str(&json).map_err(parse_error)?;
+        if envelope.format != FORMAT {
+            return Err(BundleError::Format(envelope.format));
+        }
+        if envelope.version > VERSION {
+            return Err(BundleError::Version(envelope.version));
+        }
+        let actual = checksum(serde_json::to_string(&envelope.contents).map_err(parse_error)?.as_bytes());
+        if actual != envelope.checksum {
+            return Err(BundleError::Checksum { expected: envelope.checksum, actual });
+        }
+
+        let mut bundle: Bundle = serde_json::from_value(envelope.contents).map_err(parse_error)?;
+        bundle.profile.name = bundle.name.clone();
+        bundle.profile.validate().map_err(BundleError::Profile)?;
+        Ok(bundle)
+    }
+
+    // Save the bundled profile (and history) locally and return the profile to use
+    pub fn import(mut self, mode: ImportMode) -> Result<Profile, BundleError> {
+        let local = match Profile::load(&self.name) {
+            Ok(local) => Some(local),
+            Err(ProfileError::Io(_, err)) if err.kind() == io::ErrorKind::NotFound => None,
+            // A local file that doesn't load is never overwritten, it may only need fixing by hand
+            Err(err) => {
+                let name = unused_name(&format!("{}-imported", self.name));
+                println!("Profile '{}' doesn't load ({}), importing as '{}'", self.name, err, name);
+                self.rename(&name);
+                None
+            },
+        };
+        match (&mode, local) {
+            (ImportMode::Merge { fields }, local) => {
+                self.profile = merge(local.as_ref().unwrap_or(&self.profile), &self.profile, fields)?;
+            },
+            (ImportMode::KeepBoth, Some(local)) if local != self.profile => {
+                let name = unused_name(&format!("{}-imported", self.name));
+                println!("Profile '{}' already exists, importing as '{}'", self.name, name);
+                self.rename(&name);
+            },
+            _ => {},
+        }
+        self.profile.save().map_err(BundleError::Profile)?;
+        profile::write_active_name(&self.profile.name);
+
+        if let Some(imported) = self.history {
+            let io_error = |e| BundleError::Io(history::path(), e);
+            if mode == ImportMode::Replace {
+                history::retain(|record| record.profile_name != self.profile.name).map_err(io_error)?;
+            }
+            // Appended rather than rewritten, so local lines that don't parse (yet) are never lost
+            let local = history::load();
+            let new: Vec<SessionRecord> = imported.into_iter()
+                .filter(|record| !local.iter().any(|local| local.timestamp == record.timestamp && local.session == record.session))
+                .collect();
+            history::append_all(&new).map_err(io_error)?;
+            println!("Imported {} history records", new.len());
+        }
+        Ok(self.profile)
+    }
+
+    fn rename(&mut self, name: &str) {
+        for record in self.history.iter_mut().flatten().filter(|record| record.profile_name == self.name) {
+            record.profile_name = name.to_string();
+            record.profile.name = name.to_string();
+        }
+        self.name = name.to_string();
+        self.profile.name = name.to_string();
+    }
+}
+
+// The local profile with `fields` taken from the incoming one
+fn merge(local: &Profile, incoming: &Profile, fields: &[String]) -> Result<Profile, BundleError> {
+    let to_value = |profile| serde_json::to_value(profile).map_err(|err| BundleError::Merge(err.to_string()));
+    let (mut merged, incoming_value) = (to_value(local)?, to_value(incoming)?);
+    for field in fields {
+        let value = incoming_value.get(field).ok_or_else(|| BundleError::Merge(format!("unknown profile field {:?}", field)))?;
+        merged[field.as_str()] = value.clone();
+    }
+    let mut merged: Profile = serde_json::from_value(merged).map_err(|err| BundleError::Merge(err.to_string()))?;
+    merged.name = local.name.clone();
+    merged.validate().map_err(BundleError::Profile)?;
+    Ok(merged)
+}
+
+fn unused_name(base: &str) -> String {
+    (1..).map(|i| if i == 1 { base.to_string() } else { format!("{}-{}", base, i) })
+        .find(|name| !Profile::path(name).exists())
+        .unwrap()
+}
+
+// `--export-bundle <path>` writes the profile and exits, with `--bundle-history <profile|all>` to include history;
+// `--import-bundle <path>` with `--bundle-mode <merge|keep-both|replace>` (default merge, of the comma-separated
+// `--bundle-fields` if given) switches this session to the import
+pub fn run_cli(profile: &mut Profile, arg_value: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(path) = arg_value("--import-bundle") {
+        let mode = match arg_value("--bundle-mode").as_deref() {
+            Some("replace") => ImportMode::Replace,
+            Some("keep-both") => ImportMode::KeepBoth,
+            _ => ImportMode::Merge { fields: match arg_value("--bundle-fields") {
+                Some(fields) => fields.split(',').map(|field| field.trim().to_string()).collect(),
+                None => DEFAULT_MERGE_FIELDS.iter().map(|field| field.to_string()).collect(),
+            } },
+        };
+        match Bundle::read(path.as_ref()).and_then(|bundle| bundle.import(mode)) {
+            Ok(imported) => {
+                println!("Imported profile '{}' from {}", imported.name, path);
+                *profile = imported;
+            },
+            Err(err) => eprintln!("Failed to import {}: {}", path, err),
+        }
+    }
+
+    let Some(path) = arg_value("--export-bundle") else { return false };
+    let history = arg_value("--bundle-history").map(|scope| scope == "all");
+    match Bundle::new(profile, history).write(path.as_ref()) {
+        Ok(()) => println!("Wrote bundle for profile '{}' to {}", profile.name, path),
+        Err(err) => eprintln!("Failed to export {}: {}", path, err),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_changes_with_any_flipped_bit() {
+        let bytes = br#"{"name":"default","profile":{"sensitivity_cm_per_360":30.0}}"#;
+        let original = checksum(bytes);
+        for i in 0..bytes.len() {
+            for bit in 0..8 {
+                let mut flipped = bytes.to_vec();
+                flipped[i] ^= 1 << bit;
+                assert_ne!(checksum(&flipped), original, "byte {} bit {}", i, bit);
+            }
+        }
+    }
+
+    #[test]
+    fn read_detects_an_edited_bundle() {
+        let path = std::env::temp_dir().join(format!("neurocurve_bundle_{}.json", std::process::id()));
+        let profile = Profile { sensitivity_cm_per_360: 31.5, ..Profile::default() };
+        Bundle::new(&profile, None).write(&path).unwrap();
+        assert_eq!(Bundle::read(&path).unwrap().profile, profile);
+
+        // Reformatting keeps the checksum valid, changing a value doesn't
+        let json = fs::read_to_string(&path).unwrap();
+        let envelope: serde_json::Value = serde_json::from_str(&json).unwrap();
+        fs::write(&path, serde_json::to_string(&envelope).unwrap()).unwrap();
+        assert!(Bundle::read(&path).is_ok());
+        fs::write(&path, json.replacen("31.5", "32.5", 1)).unwrap();
+        let result = Bundle::read(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(BundleError::Checksum { .. })));
+    }
+
+    #[test]
+    fn merge_takes_only_the_named_fields() {
+        let local = Profile { name: "mine".to_string(), sensitivity_cm_per_360: 40.0, mouse_dpi: 800.0, ..Profile::default() };
+        let incoming = Profile { sensitivity_cm_per_360: 25.0, mouse_dpi: 3200.0, camera_fov: 103.0, ..Profile::default() };
+        let fields: Vec<String> = DEFAULT_MERGE_FIELDS.iter().map(|field| field.to_string()).collect();
+        let merged = merge(&local, &incoming, &fields).unwrap();
+        assert_eq!(merged.name, "mine");
+        assert_eq!((merged.sensitivity_cm_per_360, merged.camera_fov), (25.0, 103.0));
+        assert_eq!(merged.mouse_dpi, 800.0);
+
+        let merged = merge(&local, &incoming, &["mouse_dpi".to_string()]).unwrap();
+        assert_eq!((merged.sensitivity_cm_per_360, merged.mouse_dpi), (40.0, 3200.0));
+        assert!(matches!(merge(&local, &incoming, &["sensitivity".to_string()]), Err(BundleError::Merge(_))));
+    }
+}
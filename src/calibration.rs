@@ -0,0 +1,379 @@
+use bevy::prelude::*;
+
+use crate::{flick, playlist, profile::{self, Profile}, rng, scoring::SessionResults, start_scenario_sequence, ScenarioState, Target};
+
+const GOLDEN_RATIO: f32 = 0.618_034; // (sqrt(5) - 1) / 2
+const SEARCH_SPAN: f32 = 2.0; // Search from cm/360 / SPAN up to cm/360 * SPAN
+const CALIBRATION_BLOCKS: usize = 6; // Full scenario sequences per calibration
+const MIN_CM_PER_360: f32 = 0.5;
+const MAX_CM_PER_360: f32 = 500.0;
+const MAX_ENDPOINT_CORRECTION: f32 = 0.5; // Limit each block's bias correction to cm/360 * 0.5..1.5
+// Two-sided 95% Student's t quantiles for 1..10 degrees of freedom, normal beyond
+const T_95: [f64; 10] = [12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228];
+
+// Golden-section search maximising block score over ln(cm/360), assuming the score is unimodal
+#[derive(Debug, Clone)]
+pub struct GoldenSectionSearch {
+    low: f32,
+    high: f32,
+    lower_probe: (f32, Option<f32>),
+    upper_probe: (f32, Option<f32>),
+    max_blocks: usize,
+    pub samples: Vec<(f32, f32)>, // (cm/360, score) in evaluation order
+}
+
+impl GoldenSectionSearch {
+    pub fn new(center_cm: f32, span: f32, max_blocks: usize) -> Self {
+        let low = (center_cm / span).max(MIN_CM_PER_360).ln();
+        let high = (center_cm * span).min(MAX_CM_PER_360).ln();
+        Self {
+            low,
+            high,
+            lower_probe: (high - GOLDEN_RATIO * (high - low), None),
+            upper_probe: (low + GOLDEN_RATIO * (high - low), None),
+            max_blocks,
+            samples: Vec::new(),
+        }
+    }
+
+    // Next cm/360 to run a block at, or None once the block budget is spent
+    pub fn next_candidate(&self) -> Option<f32> {
+        if self.samples.len() >= self.max_blocks {
+            return None;
+        }
+        let probe = if self.lower_probe.1.is_none() { self.lower_probe.0 } else { self.upper_probe.0 };
+        Some(probe.exp())
+    }
+
+    // Record the score of the block run at the last candidate and narrow the bracket
+    pub fn record(&mut self, score: f32) {
+        let Some(cm) = self.next_candidate() else { return };
+        self.samples.push((cm, score));
+
+        if self.lower_probe.1.is_none() {
+            self.lower_probe.1 = Some(score);
+        } else {
+            self.upper_probe.1 = Some(score);
+        }
+
+        let (Some(lower_score), Some(upper_score)) = (self.lower_probe.1, self.upper_probe.1) else { return };
+        if lower_score >= upper_score {
+            // Optimum is below the upper probe
+            self.high = self.upper_probe.0;
+            self.upper_probe = self.lower_probe;
+            self.lower_probe = (self.high - GOLDEN_RATIO * (self.high - self.low), None);
+        } else {
+            // Optimum is above the lower probe
+            self.low = self.lower_probe.0;
+            self.lower_probe = self.upper_probe;
+            self.upper_probe = (self.low + GOLDEN_RATIO * (self.high - self.low), None);
+        }
+    }
+
+    // Best scored probe still inside the bracket, along with the bracket
+    pub fn result(&self) -> Option<CalibrationResult> {
+        let best = [self.lower_probe, self.upper_probe].into_iter()
+            .filter_map(|(x, score)| score.map(|score| (x, score)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        Some(CalibrationResult {
+            recommended_cm: best.0.exp(),
+            best_probe_cm: best.0.exp(),
+            bracket: (self.low.exp(), self.high.exp()),
+            peak: score_peak(&self.samples),
+            samples: self.samples.clone(),
+            unbiased_cm: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CalibrationResult {
+    pub recommended_cm: f32, // What saving applies: the best probe, blended with the flick endpoint estimate if any
+    pub best_probe_cm: f32,  // Best scoring cm/360 of the search
+    pub bracket: (f32, f32), // cm/360 range the search narrowed to
+    pub peak: Option<ScorePeak>,
+    pub samples: Vec<(f32, f32)>,
+    pub unbiased_cm: Option<f32>, // cm/360 at which primary flick movements would land on target on average
+}
+
+// Peak of a quadratic fit to the block scores, with its 95% confidence interval, all in cm/360
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScorePeak {
+    pub cm: f32,
+    pub low: f32,
+    pub high: f32,
+}
+
+impl CalibrationResult {
+    // Where the scores alone put the optimum: the fitted peak and its confidence interval, or the best probe and
+    // search bracket when the scores didn't give one
+    fn score_text(&self) -> String {
+        match self.peak {
+            Some(peak) => format!("scores peak at {:.1}, 95% CI {:.1}-{:.1}", peak.cm, peak.low, peak.high),
+            None => format!("best scoring {:.1}, no CI, search bracket {:.1}-{:.1}", self.best_probe_cm, self.bracket.0, self.bracket.1),
+        }
+    }
+}
+
+// Fit score = a + b x + c x^2 over x = ln(cm/360) and give the peak -b / 2c with a 95% confidence interval, from the
+// residual variance propagated through the fit (delta method). None with fewer than 4 probes or no peak.
+pub fn score_peak(samples: &[(f32, f32)]) -> Option<ScorePeak> {
+    let dof = samples.len().checked_sub(3).filter(|&dof| dof > 0)?;
+    // Centre x so the normal equations stay well conditioned
+    let mean_x = samples.iter().map(|&(cm, _)| (cm as f64).ln()).sum::<f64>() / samples.len() as f64;
+    let rows: Vec<([f64; 3], f64)> = samples.iter().map(|&(cm, score)| {
+        let x = (cm as f64).ln() - mean_x;
+        ([1.0, x, x * x], score as f64)
+    }).collect();
+
+    let mut xtx = [[0.0; 3]; 3];
+    let mut xty = [0.0; 3];
+    for (row, y) in &rows {
+        for i in 0..3 {
+            xty[i] += row[i] * y;
+            for j in 0..3 {
+                xtx[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    let inverse = invert3(&xtx)?;
+    let beta: [f64; 3] = std::array::from_fn(|i| (0..3).map(|j| inverse[i][j] * xty[j]).sum());
+    let [_, b, c] = beta;
+    if c >= 0.0 {
+        return None;
+    }
+
+    let residual_variance = rows.iter().map(|(row, y)| {
+        let fitted: f64 = (0..3).map(|i| row[i] * beta[i]).sum();
+        (y - fitted).powi(2)
+    }).sum::<f64>() / dof as f64;
+    let optimum = -b / (2.0 * c);
+    let gradient = [0.0, -1.0 / (2.0 * c), b / (2.0 * c * c)];
+    let variance = residual_variance * (0..3).flat_map(|i| (0..3).map(move |j| (i, j)))
+        .map(|(i, j)| gradient[i] * inverse[i][j] * gradient[j]).sum::<f64>();
+    let half_width = T_95.get(dof - 1).copied().unwrap_or(1.96) * variance.max(0.0).sqrt();
+
+    let cm = |x: f64| ((x + mean_x).exp() as f32).clamp(MIN_CM_PER_360, MAX_CM_PER_360);
+    Some(ScorePeak { cm: cm(optimum), low: cm(optimum - half_width), high: cm(optimum + half_width) })
+}
+
+fn invert3(m: &[[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let cofactor = |i: usize, j: usize| {
+        let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+        let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let det = (0..3).map(|j| m[0][j] * cofactor(0, j)).sum::<f64>();
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    Some(std::array::from_fn(|i| std::array::from_fn(|j| cofactor(j, i) / det)))
+}
+
+// A block's score as each scenario's score relative to the same scenario in the first block, averaged, so scenarios
+// with bigger raw scores (tracking damage against clicking hits) don't outweigh the rest
+pub fn block_score(scores: &[f32], reference: &[f32]) -> f32 {
+    let relative: Vec<f32> = scores.iter().zip(reference)
+        .map(|(score, reference)| 1.0 + (score - reference) / reference.abs().max(1.0))
+        .collect();
+    relative.iter().sum::<f32>() / relative.len().max(1) as f32
+}
+
+// Overshooting by x% means the sensitivity is x% too high, so each block suggests cm/360 * (1 + bias).
+// Blocks are combined as a flick-weighted geometric mean.
+pub fn unbiased_cm_per_360(biases: &[(f32, f32, usize)]) -> Option<f32> {
+    let (sum, count) = biases.iter().fold((0.0, 0), |(sum, count), &(cm, bias, flicks)| {
+        let bias = bias.clamp(-MAX_ENDPOINT_CORRECTION, MAX_ENDPOINT_CORRECTION);
+        (sum + (cm * (1.0 + bias)).ln() * flicks as f32, count + flicks)
+    });
+    (count > 0).then(|| (sum / count as f32).exp())
+}
+
+#[derive(Resource, Default)]
+pub struct Calibration {
+    search: Option<GoldenSectionSearch>,
+    original_cm: f32,
+    biases: Vec<(f32, f32, usize)>, // (cm/360, mean primary endpoint error, flicks) per block
+    reference_scores: Vec<f32>, // Per-scenario scores of the first block, which the others are scored against
+    seed: u64, // Shared by every block, so each candidate cm/360 faces the same targets
+    pub result: Option<CalibrationResult>,
+}
+
+impl Calibration {
+    pub fn is_running(&self) -> bool {
+        self.search.is_some()
+    }
+
+    // HUD line for the calibration in progress or its pending result
+    pub fn status(&self, save_key: KeyCode) -> Option<String> {
+        if let Some(search) = &self.search {
+            let cm = search.next_candidate().unwrap_or_default();
+            return Some(format!("Calibration block {}/{} @ {:.1} cm/360",
+                                search.samples.len() + 1, search.max_blocks, cm));
+        }
+        self.result.as_ref().map(|result| format!(
+            "Recommended: {:.1} cm/360 ({}{}) - press {} to save to profile",
+            result.recommended_cm, result.score_text(),
+            result.unbiased_cm.map_or(String::new(), |cm| format!(", flick endpoints {:.1}", cm)),
+            profile::key_name(save_key)))
+    }
+}
+
+// Start calibration with the calibrate key, advance blocks as each scenario sequence completes, save the result with
+// the save key
+pub fn run_calibration(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut calibration: ResMut<Calibration>,
+    mut scenario_state: ResMut<ScenarioState>,
+    mut profile: ResMut<Profile>,
+    mut results: ResMut<SessionResults>,
+    mut commands: Commands,
+    targets: Query<Entity, With<Target>>,
+) {
+    if scenario_state.has_started {
+        return;
+    }
+
+    if !calibration.is_running() {
+        if keyboard.just_pressed(profile.keybinds.calibrate) {
+            let center_cm = profile.sensitivity_cm_per_360;
+            start(&mut calibration, center_cm, &mut scenario_state, &mut profile, &mut results, &mut commands, &targets);
+        } else if keyboard.just_pressed(profile.keybinds.save_calibration) {
+            if let Some(result) = calibration.result.take() {
+                profile.sensitivity_cm_per_360 = result.recommended_cm;
+                match profile.save() {
+                    Ok(()) => println!("Saved {:.1} cm/360 to profile '{}'", result.recommended_cm, profile.name),
+                    Err(err) => eprintln!("Failed to save profile: {}", err),
+                }
+            }
+        }
+        return;
+    }
+
+    // The previous block's scenario sequence has just completed
+    let scores: Vec<f32> = results.completed.iter().map(|card| card.score() as f32).collect();
+    if calibration.reference_scores.is_empty() {
+        calibration.reference_scores = scores.clone();
+    }
+    let score = block_score(&scores, &calibration.reference_scores);
+    let bias = flick::endpoint_bias(results.completed.iter().flat_map(|card| &card.flicks));
+    if let Some((bias, flicks)) = bias {
+        println!("Calibration block at {:.1} cm/360: primary endpoint {:+.1}% over {} flicks",
+                 profile.sensitivity_cm_per_360, bias * 100.0, flicks);
+        calibration.biases.push((profile.sensitivity_cm_per_360, bias, flicks));
+    }
+    let Some(search) = calibration.search.as_mut() else { return };
+    println!("Calibration block at {:.1} cm/360 scored {:.3} of the first block", profile.sensitivity_cm_per_360, score);
+    search.record(score);
+
+    if search.next_candidate().is_some() {
+        start_block(&mut calibration, &mut scenario_state, &mut profile, &mut results, &mut commands, &targets);
+        return;
+    }
+
+    // Meet the score optimum and the flick endpoint estimate halfway, in log space, but stay inside the bracket the
+    // scores narrowed the optimum to; the score optimum and endpoint estimate on their own are reported alongside
+    calibration.result = search.result().map(|mut result| {
+        result.unbiased_cm = unbiased_cm_per_360(&calibration.biases);
+        if let Some(unbiased) = result.unbiased_cm {
+            result.recommended_cm = (result.recommended_cm * unbiased).sqrt().clamp(result.bracket.0, result.bracket.1);
+        }
+        result
+    });
+    calibration.search = None;
+    profile.sensitivity_cm_per_360 = calibration.original_cm;
+    if let Some(result) = &calibration.result {
+        for (cm, score) in &result.samples {
+            println!("  {:.1} cm/360: {:.3}", cm, score);
+        }
+        if let Some(unbiased) = result.unbiased_cm {
+            println!("  Flick endpoints suggest {:.1} cm/360", unbiased);
+        }
+        println!("Calibration complete. Recommended {:.1} cm/360 ({})", result.recommended_cm, result.score_text());
+    }
+}
+
+// Begin a calibration searching around `center_cm`, restoring the profile's own cm/360 when it finishes
+pub fn start(calibration: &mut Calibration, center_cm: f32, scenario_state: &mut ScenarioState, profile: &mut Profile,
+             results: &mut SessionResults, commands: &mut Commands, targets: &Query<Entity, With<Target>>) {
+    calibration.search = Some(GoldenSectionSearch::new(center_cm, SEARCH_SPAN, CALIBRATION_BLOCKS));
+    calibration.original_cm = profile.sensitivity_cm_per_360;
+    calibration.result = None;
+    calibration.biases.clear();
+    calibration.reference_scores.clear();
+    calibration.seed = scenario_state.fixed_seed.unwrap_or_else(rng::new_seed);
+    println!("Starting calibration: {} blocks around {:.1} cm/360", CALIBRATION_BLOCKS, center_cm);
+    start_block(calibration, scenario_state, profile, results, commands, targets);
+}
+
+fn start_block(calibration: &mut Calibration, scenario_state: &mut ScenarioState, profile: &mut Profile,
+               results: &mut SessionResults, commands: &mut Commands, targets: &Query<Entity, With<Target>>) {
+    let Some(cm) = calibration.search.as_ref().and_then(GoldenSectionSearch::next_candidate) else { return };
+    profile.sensitivity_cm_per_360 = cm;
+    start_scenario_sequence(scenario_state, results, commands, targets, playlist::find_or_default(&profile.playlist),
+                            Some(calibration.seed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Peaks at 30 cm/360 in log space
+    fn score(cm: f32) -> f32 {
+        100.0 - 50.0 * (cm.ln() - 30f32.ln()).powi(2)
+    }
+
+    #[test]
+    fn golden_section_finds_known_optimum() {
+        let mut search = GoldenSectionSearch::new(25.0, SEARCH_SPAN, 20);
+        while let Some(cm) = search.next_candidate() {
+            search.record(score(cm));
+        }
+        let result = search.result().unwrap();
+        assert!((result.recommended_cm / 30.0 - 1.0).abs() < 0.01, "{}", result.recommended_cm);
+        assert!(result.bracket.0 < 30.1 && 29.9 < result.bracket.1, "{:?}", result.bracket);
+        assert_eq!(result.samples.len(), 20);
+    }
+
+    #[test]
+    fn interval_brackets_noisy_peak() {
+        let noise = [0.8, -0.5, 0.3, -0.9, 0.6, -0.2];
+        let samples: Vec<(f32, f32)> = [15.0, 20.0, 26.0, 34.0, 45.0, 60.0].into_iter().zip(noise)
+            .map(|(cm, noise)| (cm, score(cm) + noise)).collect();
+        let ScorePeak { cm, low, high } = score_peak(&samples).unwrap();
+        assert!(low < cm && cm < high && low < 30.0 && 30.0 < high, "{} in {}-{}", cm, low, high);
+        assert!(high / low < 1.2, "{}-{}", low, high);
+
+        // More noise, wider interval
+        let noisier: Vec<(f32, f32)> = samples.iter().zip(noise).map(|(&(cm, score), noise)| (cm, score + 10.0 * noise)).collect();
+        let ScorePeak { low: wide_low, high: wide_high, .. } = score_peak(&noisier).unwrap();
+        assert!(wide_high / wide_low > high / low);
+    }
+
+    #[test]
+    fn peak_is_exact_without_noise() {
+        let samples: Vec<(f32, f32)> = [15.0, 22.0, 40.0, 55.0].into_iter().map(|cm| (cm, score(cm))).collect();
+        let ScorePeak { cm, low, high } = score_peak(&samples).unwrap();
+        assert!((cm / 30.0 - 1.0).abs() < 1e-3 && (low / 30.0 - 1.0).abs() < 1e-3 && (high / 30.0 - 1.0).abs() < 1e-3, "{}-{}", low, high);
+    }
+
+    #[test]
+    fn peak_needs_a_maximum_and_enough_probes() {
+        let samples: Vec<(f32, f32)> = [15.0, 22.0, 40.0].into_iter().map(|cm| (cm, score(cm))).collect();
+        assert!(score_peak(&samples).is_none());
+        let valley: Vec<(f32, f32)> = [15.0, 22.0, 40.0, 55.0].into_iter().map(|cm| (cm, -score(cm))).collect();
+        assert!(score_peak(&valley).is_none());
+    }
+
+    #[test]
+    fn block_score_weighs_scenarios_equally() {
+        // Clicking up 20% beats tracking up 5%, though tracking's raw scores are far bigger
+        let reference = [10.0, 1000.0];
+        let clicking_better = block_score(&[12.0, 1000.0], &reference);
+        let tracking_better = block_score(&[10.0, 1050.0], &reference);
+        assert!((clicking_better - 1.1).abs() < 1e-6 && (tracking_better - 1.025).abs() < 1e-6);
+        assert_eq!(block_score(&reference, &reference), 1.0);
+        // A first-block score of zero or below still compares sensibly
+        assert!(block_score(&[2.0], &[-1.0]) > block_score(&[0.0], &[-1.0]));
+    }
+}
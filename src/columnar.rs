@@ -0,0 +1,148 @@
+use std::{fs, io::{self, BufWriter, Write}, path::Path};
+
+// Compact column-oriented dump for large tables, readable with a few lines of numpy:
+//   magic  b"NCCOL001"
+//   u32    column count, u64 row count
+//   per column: u8 name length, UTF-8 name, u8 type (0 f32, 1 f64, 2 i32, 3 u32, 4 u64, 5 bool as u8)
+//   then each column's values back to back in the same order
+// All integers and floats are little-endian. Missing floats are NaN, missing ids u64::MAX.
+const MAGIC: &[u8; 8] = b"NCCOL001";
+
+#[derive(Debug, PartialEq)]
+pub enum Column {
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    I32(Vec<i32>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+    Bool(Vec<bool>),
+}
+
+impl Column {
+    fn type_tag(&self) -> u8 {
+        match self {
+            Self::F32(_) => 0,
+            Self::F64(_) => 1,
+            Self::I32(_) => 2,
+            Self::U32(_) => 3,
+            Self::U64(_) => 4,
+            Self::Bool(_) => 5,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::F32(values) => values.len(),
+            Self::F64(values) => values.len(),
+            Self::I32(values) => values.len(),
+            Self::U32(values) => values.len(),
+            Self::U64(values) => values.len(),
+            Self::Bool(values) => values.len(),
+        }
+    }
+
+    fn write_values(&self, out: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::F32(values) => values.iter().try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            Self::F64(values) => values.iter().try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            Self::I32(values) => values.iter().try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            Self::U32(values) => values.iter().try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            Self::U64(values) => values.iter().try_for_each(|v| out.write_all(&v.to_le_bytes())),
+            Self::Bool(values) => values.iter().try_for_each(|&v| out.write_all(&[v as u8])),
+        }
+    }
+}
+
+pub fn write(path: &Path, columns: &[(&str, Column)]) -> io::Result<()> {
+    let rows = columns.first().map_or(0, |(_, column)| column.len());
+    if let Some((name, _)) = columns.iter().find(|(_, column)| column.len() != rows) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("column '{}' has a different length", name)));
+    }
+
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&(columns.len() as u32).to_le_bytes())?;
+    out.write_all(&(rows as u64).to_le_bytes())?;
+    for (name, column) in columns {
+        let name = &name.as_bytes()[..name.len().min(u8::MAX as usize)];
+        out.write_all(&[name.len() as u8])?;
+        out.write_all(name)?;
+        out.write_all(&[column.type_tag()])?;
+    }
+    for (_, column) in columns {
+        column.write_values(&mut out)?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    // Decode a file by the layout described at the top, independently of the writer
+    fn read(path: &Path) -> io::Result<Vec<(String, Column)>> {
+        let bytes = fs::read(path)?;
+        let mut input = bytes.as_slice();
+        let mut take = |n: usize| -> io::Result<Vec<u8>> {
+            let mut buffer = vec![0; n];
+            input.read_exact(&mut buffer)?;
+            Ok(buffer)
+        };
+        assert_eq!(take(8)?, MAGIC);
+        let count = u32::from_le_bytes(take(4)?.try_into().unwrap());
+        let rows = u64::from_le_bytes(take(8)?.try_into().unwrap()) as usize;
+        let mut headers = Vec::new();
+        for _ in 0..count {
+            let len = take(1)?[0] as usize;
+            let name = String::from_utf8(take(len)?).unwrap();
+            headers.push((name, take(1)?[0]));
+        }
+
+        let mut columns = Vec::new();
+        for (name, tag) in headers {
+            let size = [4, 8, 4, 4, 8, 1][tag as usize];
+            let values: Vec<Vec<u8>> = (0..rows).map(|_| take(size)).collect::<io::Result<_>>()?;
+            let column = match tag {
+                0 => Column::F32(values.iter().map(|v| f32::from_le_bytes(v[..].try_into().unwrap())).collect()),
+                1 => Column::F64(values.iter().map(|v| f64::from_le_bytes(v[..].try_into().unwrap())).collect()),
+                2 => Column::I32(values.iter().map(|v| i32::from_le_bytes(v[..].try_into().unwrap())).collect()),
+                3 => Column::U32(values.iter().map(|v| u32::from_le_bytes(v[..].try_into().unwrap())).collect()),
+                4 => Column::U64(values.iter().map(|v| u64::from_le_bytes(v[..].try_into().unwrap())).collect()),
+                _ => Column::Bool(values.iter().map(|v| v[0] != 0).collect()),
+            };
+            columns.push((name, column));
+        }
+        assert!(take(1).is_err(), "trailing bytes");
+        Ok(columns)
+    }
+
+    #[test]
+    fn written_columns_decode_back() {
+        let path = std::env::temp_dir().join(format!("neurocurve_columnar_{}.ncol", std::process::id()));
+        let columns = [
+            ("time", Column::F64(vec![0.0, 0.5, 1.25])),
+            ("error_x", Column::F32(vec![-0.1, 0.0, 3.5])),
+            ("delta", Column::I32(vec![-7, 0, 12])),
+            ("shot", Column::U32(vec![1, 2, 3])),
+            ("target", Column::U64(vec![42, u64::MAX, 7])),
+            ("hit", Column::Bool(vec![true, false, true])),
+        ];
+        write(&path, &columns).unwrap();
+        let decoded = read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(decoded.len(), columns.len());
+        for ((name, column), (decoded_name, decoded_column)) in columns.iter().zip(&decoded) {
+            assert_eq!((*name, column), (decoded_name.as_str(), decoded_column));
+        }
+    }
+
+    #[test]
+    fn rejects_columns_of_different_lengths() {
+        let path = std::env::temp_dir().join(format!("neurocurve_columnar_ragged_{}.ncol", std::process::id()));
+        let result = write(&path, &[("a", Column::F32(vec![1.0, 2.0])), ("b", Column::U32(vec![1]))]);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+}
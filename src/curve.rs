@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Maps hand speed (mouse counts per millisecond) to a gain multiplier on the base sensitivity
+pub trait SensitivityCurve: fmt::Debug + Send + Sync {
+    fn gain(&self, speed: f32) -> f32;
+    fn name(&self) -> &'static str;
+}
+
+// Constant gain, equivalent to no acceleration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Linear {
+    pub gain: f32,
+}
+
+impl Default for Linear {
+    fn default() -> Self {
+        Self { gain: 1.0 }
+    }
+}
+
+impl SensitivityCurve for Linear {
+    fn gain(&self, _speed: f32) -> f32 {
+        self.gain
+    }
+
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+}
+
+// Gain rises as (acceleration * (speed - offset))^(exponent - 1) above the offset, up to a cap (0 = uncapped)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Classic {
+    pub offset: f32,
+    pub acceleration: f32,
+    pub exponent: f32,
+    pub cap: f32,
+}
+
+impl Default for Classic {
+    fn default() -> Self {
+        Self { offset: 0.0, acceleration: 0.01, exponent: 2.0, cap: 0.0 }
+    }
+}
+
+impl SensitivityCurve for Classic {
+    fn gain(&self, speed: f32) -> f32 {
+        if speed <= self.offset {
+            return 1.0;
+        }
+        let gain = 1.0 + (self.acceleration * (speed - self.offset)).powf(self.exponent - 1.0);
+        apply_cap(gain, self.cap)
+    }
+
+    fn name(&self) -> &'static str {
+        "classic"
+    }
+}
+
+// Gain approaches `limit` exponentially above the offset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Natural {
+    pub offset: f32,
+    pub decay_rate: f32,
+    pub limit: f32,
+}
+
+impl Default for Natural {
+    fn default() -> Self {
+        Self { offset: 0.0, decay_rate: 0.1, limit: 1.5 }
+    }
+}
+
+impl SensitivityCurve for Natural {
+    fn gain(&self, speed: f32) -> f32 {
+        if speed <= self.offset {
+            return 1.0;
+        }
+        1.0 + (self.limit - 1.0) * (1.0 - (-self.decay_rate * (speed - self.offset)).exp())
+    }
+
+    fn name(&self) -> &'static str {
+        "natural"
+    }
+}
+
+// Gain follows output_offset + (scale * speed)^exponent, up to a cap (0 = uncapped)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Power {
+    pub scale: f32,
+    pub exponent: f32,
+    pub output_offset: f32,
+    pub cap: f32,
+}
+
+impl Default for Power {
+    fn default() -> Self {
+        Self { scale: 1.0, exponent: 0.05, output_offset: 0.0, cap: 0.0 }
+    }
+}
+
+impl SensitivityCurve for Power {
+    fn gain(&self, speed: f32) -> f32 {
+        let gain = self.output_offset + (self.scale * speed).max(0.0).powf(self.exponent);
+        apply_cap(gain, self.cap)
+    }
+
+    fn name(&self) -> &'static str {
+        "power"
+    }
+}
+
+// Piecewise-linear (speed, gain) points, clamped to the first and last gain outside the table
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LookupTable {
+    pub points: Vec<[f32; 2]>,
+}
+
+impl SensitivityCurve for LookupTable {
+    fn gain(&self, speed: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else { return 1.0 };
+        if speed <= first[0] {
+            return first[1];
+        }
+        if speed >= last[0] {
+            return last[1];
+        }
+
+        let i = self.points.partition_point(|point| point[0] <= speed);
+        let ([x0, y0], [x1, y1]) = (self.points[i - 1], self.points[i]);
+        y0 + (y1 - y0) * (speed - x0) / (x1 - x0)
+    }
+
+    fn name(&self) -> &'static str {
+        "lookup_table"
+    }
+}
+
+fn apply_cap(gain: f32, cap: f32) -> f32 {
+    if cap > 0.0 { gain.min(cap) } else { gain }
+}
+
+// Serializable selection of one of the curve implementations, as stored in the profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum CurveModel {
+    Linear(Linear),
+    Classic(Classic),
+    Natural(Natural),
+    Power(Power),
+    LookupTable(LookupTable),
+}
+
+impl Default for CurveModel {
+    fn default() -> Self {
+        Self::Linear(Linear::default())
+    }
+}
+
+impl CurveModel {
+    pub fn as_curve(&self) -> &dyn SensitivityCurve {
+        match self {
+            Self::Linear(curve) => curve,
+            Self::Classic(curve) => curve,
+            Self::Natural(curve) => curve,
+            Self::Power(curve) => curve,
+            Self::LookupTable(curve) => curve,
+        }
+    }
+
+    // Reject parameters that would produce a negative, non-finite or ill-defined gain
+    pub fn validate(&self) -> Result<(), String> {
+        let finite = |values: &[f32]| values.iter().all(|v| v.is_finite());
+        match self {
+            Self::Linear(c) if !finite(&[c.gain]) || c.gain <= 0.0 => Err("linear gain must be positive".into()),
+            Self::Classic(c) if !finite(&[c.offset, c.acceleration, c.exponent, c.cap]) || c.offset < 0.0
+                || c.acceleration < 0.0 || c.exponent <= 1.0 || c.cap < 0.0 =>
+                // At exponent 1 the gain would jump straight from 1 to 2 past the offset
+                Err("classic needs offset >= 0, acceleration >= 0, exponent > 1 and cap >= 0".into()),
+            Self::Natural(c) if !finite(&[c.offset, c.decay_rate, c.limit]) || c.offset < 0.0
+                || c.decay_rate <= 0.0 || c.limit <= 0.0 =>
+                Err("natural needs offset >= 0, decay_rate > 0 and limit > 0".into()),
+            Self::Power(c) if !finite(&[c.scale, c.exponent, c.output_offset, c.cap]) || c.scale <= 0.0
+                || c.exponent < 0.0 || c.output_offset < 0.0 || c.cap < 0.0 =>
+                Err("power needs scale > 0, exponent >= 0, output_offset >= 0 and cap >= 0".into()),
+            Self::LookupTable(c) if c.points.is_empty() => Err("lookup table needs at least one point".into()),
+            Self::LookupTable(c) if c.points.iter().any(|p| !finite(p) || p[1] <= 0.0) =>
+                Err("lookup table gains must be positive".into()),
+            Self::LookupTable(c) if c.points.windows(2).any(|w| w[1][0] <= w[0][0]) =>
+                Err("lookup table speeds must be strictly increasing".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl SensitivityCurve for CurveModel {
+    fn gain(&self, speed: f32) -> f32 {
+        self.as_curve().gain(speed)
+    }
+
+    fn name(&self) -> &'static str {
+        self.as_curve().name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn classic_gain_at_known_speeds() {
+        let curve = Classic { offset: 2.0, acceleration: 0.1, exponent: 3.0, cap: 1.5 };
+        assert_eq!(curve.gain(0.0), 1.0);
+        assert_eq!(curve.gain(2.0), 1.0);
+        // 1 + (0.1 * 5)^2
+        assert!(close(curve.gain(7.0), 1.25));
+        // 1 + (0.1 * 10)^2 = 2, capped
+        assert_eq!(curve.gain(12.0), 1.5);
+        assert!(close(Classic { cap: 0.0, ..curve }.gain(12.0), 2.0));
+    }
+
+    #[test]
+    fn natural_gain_approaches_limit() {
+        let curve = Natural { offset: 1.0, decay_rate: 0.5, limit: 2.0 };
+        assert_eq!(curve.gain(1.0), 1.0);
+        // 1 + (2 - 1) * (1 - e^-1)
+        assert!(close(curve.gain(3.0), 2.0 - (-1f32).exp()));
+        assert!(close(curve.gain(1000.0), 2.0));
+    }
+
+    #[test]
+    fn power_gain_at_known_speeds() {
+        let curve = Power { scale: 0.5, exponent: 0.5, output_offset: 0.25, cap: 2.0 };
+        assert_eq!(curve.gain(0.0), 0.25);
+        // 0.25 + (0.5 * 8)^0.5 = 2.25, capped
+        assert_eq!(curve.gain(8.0), 2.0);
+        assert!(close(curve.gain(2.0), 1.25));
+        assert_eq!(curve.gain(-4.0), 0.25);
+    }
+
+    #[test]
+    fn lookup_table_interpolates_and_clamps() {
+        let table = LookupTable { points: vec![[2.0, 1.0], [6.0, 2.0], [10.0, 2.5]] };
+        assert_eq!(table.gain(0.0), 1.0);
+        assert!(close(table.gain(3.0), 1.25));
+        assert_eq!(table.gain(6.0), 2.0);
+        assert!(close(table.gain(9.0), 2.375));
+        assert_eq!(table.gain(50.0), 2.5);
+        assert_eq!(LookupTable::default().gain(5.0), 1.0);
+        assert_eq!(Linear { gain: 1.3 }.gain(42.0), 1.3);
+    }
+
+    #[test]
+    fn validate_rejects_invalid_parameters() {
+        let valid = [
+            CurveModel::default(),
+            CurveModel::Classic(Classic::default()),
+            CurveModel::Natural(Natural::default()),
+            CurveModel::Power(Power::default()),
+            CurveModel::LookupTable(LookupTable { points: vec![[0.0, 1.0], [5.0, 1.5]] }),
+        ];
+        for curve in valid {
+            assert!(curve.validate().is_ok(), "{:?}", curve);
+        }
+
+        let invalid = [
+            CurveModel::Linear(Linear { gain: 0.0 }),
+            CurveModel::Linear(Linear { gain: f32::INFINITY }),
+            CurveModel::Classic(Classic { exponent: 1.0, ..Classic::default() }),
+            CurveModel::Classic(Classic { offset: -1.0, ..Classic::default() }),
+            CurveModel::Classic(Classic { cap: f32::NAN, ..Classic::default() }),
+            CurveModel::Natural(Natural { decay_rate: 0.0, ..Natural::default() }),
+            CurveModel::Natural(Natural { limit: -1.0, ..Natural::default() }),
+            CurveModel::Power(Power { scale: 0.0, ..Power::default() }),
+            CurveModel::Power(Power { exponent: -0.5, ..Power::default() }),
+            CurveModel::LookupTable(LookupTable::default()),
+            CurveModel::LookupTable(LookupTable { points: vec![[0.0, 1.0], [5.0, 0.0]] }),
+            CurveModel::LookupTable(LookupTable { points: vec![[5.0, 1.0], [5.0, 1.5]] }),
+        ];
+        for curve in invalid {
+            assert!(curve.validate().is_err(), "{:?}", curve);
+        }
+    }
+}
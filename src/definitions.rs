@@ -0,0 +1,44 @@
+use serde::de::DeserializeOwned;
+use std::{fs, path::Path};
+
+// Definitions shipped with the binary as (file name, TOML), then every `*.toml` in `dir` in file name order, each
+// replacing a definition of the same name (ignoring case) or adding a new one. Definitions without a name take their
+// file name. Broken files in `dir` are skipped with a message; a broken built-in one is a bug.
+pub fn load_dir<T: DeserializeOwned>(kind: &str, built_in: &[(&str, &str)], dir: &Path,
+                                     name_of: fn(&mut T) -> &mut String, validate: fn(&T) -> Result<(), String>) -> Vec<T> {
+    let parse = |file: &str, contents: &str| -> Result<T, String> {
+        let mut def: T = toml::from_str(contents).map_err(|err| err.to_string())?;
+        if name_of(&mut def).is_empty() {
+            *name_of(&mut def) = file.trim_end_matches(".toml").to_string();
+        }
+        validate(&def)?;
+        Ok(def)
+    };
+
+    let mut defs: Vec<T> = built_in.iter()
+        .map(|(file, contents)| parse(file, contents).unwrap_or_else(|err| panic!("built-in {} {}: {}", kind, file, err)))
+        .collect();
+
+    let Ok(entries) = fs::read_dir(dir) else { return defs };
+    let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let file = path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+        let mut def = match fs::read_to_string(&path).map_err(|err| err.to_string()).and_then(|contents| parse(&file, &contents)) {
+            Ok(def) => def,
+            Err(err) => {
+                eprintln!("Skipping {} {}: {}", kind, path.display(), err);
+                continue;
+            },
+        };
+        let name = name_of(&mut def).clone();
+        match defs.iter_mut().position(|existing| name_of(existing).eq_ignore_ascii_case(&name)) {
+            Some(index) => defs[index] = def,
+            None => defs.push(def),
+        }
+    }
+    defs
+}
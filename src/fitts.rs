@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::flick;
+
+const MIN_SAMPLES: usize = 5;
+const EFFECTIVE_WIDTH_SCALE: f32 = 4.133; // sqrt(2 * pi * e), so We covers 96% of endpoints
+
+// One kill in a clicking scenario, as a pointing movement from the previous shot to the target
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FittsSample {
+    pub distance: f32,      // Radians from the crosshair at the previous shot to the target
+    pub width: f32,         // Angular diameter of the target in radians
+    pub movement_time: f32, // Seconds from the previous shot (or the target's spawn, if later) to the kill
+    pub deviation: f32,     // Click position past the target centre along the movement, radians
+}
+
+impl FittsSample {
+    // Shannon formulation, in bits
+    pub fn index_of_difficulty(&self) -> f32 {
+        (self.distance / self.width + 1.0).log2()
+    }
+}
+
+// Kill at `target` after moving from where the camera pointed along `previous_forward`
+pub fn sample(camera: &Transform, previous_forward: Vec3, target: Vec3, target_radius: f32, movement_time: f32) -> FittsSample {
+    let error = flick::angular_offset(camera, target);
+    let start = flick::angular_offset(camera, camera.translation + previous_forward);
+    let movement = error - start;
+    let distance = movement.length();
+    FittsSample {
+        distance,
+        width: 2.0 * (target_radius / target.distance(camera.translation).max(target_radius)).asin(),
+        movement_time,
+        deviation: if distance > 0.0 { -error.dot(movement) / distance } else { 0.0 },
+    }
+}
+
+// Least-squares MT = a + b * ID, plus effective throughput from the spread of click positions
+#[derive(Debug, Clone, Copy)]
+pub struct FittsFit {
+    pub intercept: f32,  // a, seconds
+    pub slope: f32,      // b, seconds per bit
+    pub throughput: f32, // Bits/s using the effective width
+    pub samples: usize,
+}
+
+pub fn fit(samples: &[FittsSample]) -> Option<FittsFit> {
+    let samples: Vec<&FittsSample> = samples.iter().filter(|s| s.movement_time > 0.0 && s.width > 0.0).collect();
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+    let n = samples.len() as f32;
+    let mean_id = samples.iter().map(|s| s.index_of_difficulty()).sum::<f32>() / n;
+    let mean_time = samples.iter().map(|s| s.movement_time).sum::<f32>() / n;
+    let (covariance, variance) = samples.iter().fold((0.0, 0.0), |(cov, var), s| {
+        let id = s.index_of_difficulty() - mean_id;
+        (cov + id * (s.movement_time - mean_time), var + id * id)
+    });
+    // All kills at the same difficulty leave the slope undefined; treat time as flat
+    let slope = if variance > f32::EPSILON { covariance / variance } else { 0.0 };
+
+    let mean_deviation = samples.iter().map(|s| s.deviation).sum::<f32>() / n;
+    let spread = (samples.iter().map(|s| (s.deviation - mean_deviation).powi(2)).sum::<f32>() / (n - 1.0)).sqrt();
+    let effective_width = EFFECTIVE_WIDTH_SCALE * spread;
+    let effective_id = if effective_width > f32::EPSILON {
+        samples.iter().map(|s| (s.distance / effective_width + 1.0).log2()).sum::<f32>() / n
+    } else {
+        mean_id
+    };
+
+    Some(FittsFit { intercept: mean_time - slope * mean_id, slope, throughput: effective_id / mean_time, samples: samples.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kill(distance: f32, width: f32, deviation: f32) -> FittsSample {
+        let id = (distance / width + 1.0).log2();
+        FittsSample { distance, width, movement_time: 0.2 + 0.15 * id, deviation }
+    }
+
+    #[test]
+    fn fit_recovers_linear_law() {
+        let deviations = [0.002, -0.002, 0.001, -0.001, 0.0, 0.003, -0.003];
+        let samples: Vec<FittsSample> = [0.1, 0.2, 0.4, 0.8, 1.2, 0.3, 0.6].into_iter().zip(deviations)
+            .map(|(distance, deviation)| kill(distance, 0.02, deviation))
+            .collect();
+
+        let fit = fit(&samples).unwrap();
+        assert!((fit.intercept - 0.2).abs() < 1e-4, "{}", fit.intercept);
+        assert!((fit.slope - 0.15).abs() < 1e-4, "{}", fit.slope);
+        assert_eq!(fit.samples, samples.len());
+
+        let n = samples.len() as f32;
+        let spread = (deviations.iter().map(|d| d * d).sum::<f32>() / (n - 1.0)).sqrt();
+        let effective_id = samples.iter().map(|s| (s.distance / (EFFECTIVE_WIDTH_SCALE * spread) + 1.0).log2()).sum::<f32>() / n;
+        let mean_time = samples.iter().map(|s| s.movement_time).sum::<f32>() / n;
+        assert!((fit.throughput - effective_id / mean_time).abs() < 1e-3);
+    }
+
+    #[test]
+    fn fit_needs_enough_timed_kills() {
+        let samples: Vec<FittsSample> = (1..=4).map(|i| kill(i as f32 * 0.1, 0.02, 0.0)).collect();
+        assert!(fit(&samples).is_none());
+        let untimed = FittsSample { movement_time: 0.0, ..kill(0.5, 0.02, 0.0) };
+        assert!(fit(&[samples, vec![untimed]].concat()).is_none());
+    }
+
+    #[test]
+    fn fit_is_flat_at_a_single_difficulty() {
+        let samples: Vec<FittsSample> = [0.001, -0.001, 0.002, -0.002, 0.0].into_iter().map(|d| kill(0.4, 0.02, d)).collect();
+        let fit = fit(&samples).unwrap();
+        assert_eq!(fit.slope, 0.0);
+        assert!((fit.intercept - samples[0].movement_time).abs() < 1e-5);
+    }
+}
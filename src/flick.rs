@@ -0,0 +1,346 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{curve::{CurveModel, LookupTable}, profile::Profile, shots::ShotLog, ScenarioEvent, ScenarioState};
+
+const MIN_FLICK_AMPLITUDE: f32 = 0.5 * std::f32::consts::PI / 180.0; // Ignore shots with less than 0.5 degrees of movement
+const MIN_SAMPLES_PER_BIN: usize = 5;
+const MAX_BINS: usize = 6;
+const MAX_OVERSHOOT_CORRECTION: f32 = 0.5; // Limit per-fit gain changes to 1/1.5..1/0.5
+const CORRECTION_TIME_SCALE: f32 = 0.1; // Seconds of correction that halve a flick's weight
+const MOVEMENT_THRESHOLD: f32 = 0.1; // Fraction of peak angular speed below which the crosshair counts as still
+const MAX_PATH_POINTS: usize = 1 << 14; // Path kept between shots, oldest half dropped past this
+
+// One shot and the mouse movement that led up to it
+#[derive(Debug, Clone, Copy)]
+pub struct FlickSample {
+    pub peak_speed: f32,        // counts/ms
+    pub radians_per_count: f32, // Sensitivity (base * curve gain) at the peak, without the FOV match scale
+    pub amplitude: f32,         // Radians turned over the flick (since the killed target spawned, else the previous shot)
+    pub direction: Vec2,        // Unit direction of that movement on screen (right, up)
+    pub error: Vec2,            // Target offset from the crosshair at the click, radians (right, up)
+    pub correction_time: f32,   // Seconds between peak speed and the click
+    pub hit: bool,
+    pub aiming_down_sights: bool, // ADS at any point of the flick
+    pub segmentation: Option<FlickSegmentation>, // Kills only, over the path since the target spawned
+}
+
+// Crosshair position after one motion event, relative to where it was at the previous shot
+#[derive(Debug, Clone, Copy)]
+pub struct PathPoint {
+    pub time: f32,
+    pub position: Vec2,         // Radians (right, up)
+    pub speed: f32,             // Angular speed in radians/s
+    pub counts_per_ms: f32,     // Mouse speed
+    pub radians_per_count: f32, // Base * curve gain at this event, without the FOV match scale
+    pub aiming_down_sights: bool,
+}
+
+// A flick split into its ballistic primary movement and the corrective sub-movements after it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FlickSegmentation {
+    pub distance: f32,       // Radians from the start of the path to the target
+    pub endpoint_error: f32, // Primary movement end past the target as a fraction of distance, negative when short
+    pub corrections: u32,
+    pub primary_time: f32,   // Seconds from movement onset to the end of the primary movement
+    pub settle_time: f32,    // Seconds from the end of the primary movement to the end of the last correction
+}
+
+// Split the path at the first speed minimum after peak speed; every later speed peak above the threshold
+// is a correction. Speeds get a 3-point moving average so single-event jitter doesn't count.
+pub fn segment_flick(points: &[PathPoint], start: Vec2, goal: Vec2) -> Option<FlickSegmentation> {
+    let distance = goal.distance(start);
+    if points.len() < 3 || distance < MIN_FLICK_AMPLITUDE {
+        return None;
+    }
+    let direction = (goal - start) / distance;
+
+    let speeds: Vec<f32> = (0..points.len()).map(|i| {
+        let window = &points[i.saturating_sub(1)..(i + 2).min(points.len())];
+        window.iter().map(|point| point.speed).sum::<f32>() / window.len() as f32
+    }).collect();
+    let (peak, peak_speed) = speeds.iter().copied().enumerate().max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let threshold = peak_speed * MOVEMENT_THRESHOLD;
+    let onset = speeds[..peak].iter().rposition(|&speed| speed < threshold).map_or(0, |i| i + 1);
+    let primary_end = (peak + 1..speeds.len())
+        .find(|&i| speeds[i] < threshold || speeds.get(i + 1).is_some_and(|&next| next > speeds[i]))
+        .unwrap_or(speeds.len() - 1);
+
+    let is_correction = |i: usize| speeds[i] >= threshold && speeds[i] > speeds[i - 1]
+        && speeds.get(i + 1).is_none_or(|&next| speeds[i] >= next);
+    let corrections = (primary_end + 1..speeds.len()).filter(|&i| is_correction(i)).count() as u32;
+    let settled = (primary_end + 1..speeds.len()).rev().find(|&i| speeds[i] >= threshold).unwrap_or(primary_end);
+
+    let amplitude = (points[primary_end].position - start).dot(direction);
+    Some(FlickSegmentation {
+        distance,
+        endpoint_error: amplitude / distance - 1.0,
+        corrections,
+        primary_time: points[primary_end].time - points[onset].time,
+        settle_time: points[settled].time - points[primary_end].time,
+    })
+}
+
+impl FlickSample {
+    pub fn angular_error(&self) -> f32 {
+        self.error.length()
+    }
+
+    // Crosshair overshoot past the target as a fraction of the flick, negative when short
+    pub fn overshoot(&self) -> f32 {
+        -self.error.dot(self.direction) / self.amplitude
+    }
+
+    // Whether fit_curve uses this flick: it moved the mouse and turned at the hipfire scale
+    pub fn fittable(&self) -> bool {
+        self.peak_speed > 0.0 && !self.aiming_down_sights
+    }
+}
+
+// Mouse movement accumulated since the last shot or scenario start
+#[derive(Resource, Default)]
+pub struct FlickTracker {
+    path: Vec2,
+    points: Vec<PathPoint>,
+}
+
+impl FlickTracker {
+    // `turn` is the camera rotation for one motion event in radians, screen right/up, at time `now`.
+    // `radians_per_count` is base * curve gain; `scale` is the FOV match (and ADS) factor the turn also includes.
+    pub fn record_motion(&mut self, speed: f32, turn: Vec2, radians_per_count: f32, scale: f32,
+                         aiming_down_sights: bool, now: f32) {
+        self.path += turn;
+        if self.points.len() == MAX_PATH_POINTS {
+            self.points.drain(..MAX_PATH_POINTS / 2);
+        }
+        self.points.push(PathPoint { time: now, position: self.path, speed: speed * 1000.0 * radians_per_count * scale,
+                                     counts_per_ms: speed, radians_per_count, aiming_down_sights });
+    }
+
+    // Close the current flick at a click with the given target offset.
+    // `spawned` is the killed target's spawn time; the flick is the path from there, so earlier movement doesn't count.
+    pub fn finish_shot(&mut self, error: Option<Vec2>, hit: bool, now: f32, spawned: Option<f32>) -> Option<FlickSample> {
+        let tracker = std::mem::take(self);
+        let first = spawned.map_or(0, |spawned| tracker.points.partition_point(|point| point.time < spawned));
+        let start = first.checked_sub(1).map_or(Vec2::ZERO, |i| tracker.points[i].position);
+        let points = &tracker.points[first..];
+        let path = tracker.path - start;
+        let amplitude = path.length();
+        if amplitude < MIN_FLICK_AMPLITUDE {
+            return None;
+        }
+        let peak = points.iter().max_by(|a, b| a.counts_per_ms.total_cmp(&b.counts_per_ms))?;
+        let error = error?;
+
+        Some(FlickSample {
+            peak_speed: peak.counts_per_ms,
+            radians_per_count: peak.radians_per_count,
+            amplitude,
+            direction: path / amplitude,
+            error,
+            correction_time: now - peak.time,
+            hit,
+            aiming_down_sights: points.iter().any(|point| point.aiming_down_sights),
+            segmentation: spawned.and_then(|_| segment_flick(points, start, tracker.path + error)),
+        })
+    }
+}
+
+// Movement before a scenario (countdowns, the previous scenario) isn't part of its first flick
+pub fn reset_on_scenario_start(mut events: EventReader<ScenarioEvent>, mut tracker: ResMut<FlickTracker>) {
+    if events.read().any(|event| matches!(event, ScenarioEvent::Started)) {
+        *tracker = FlickTracker::default();
+    }
+}
+
+// Target offset from the crosshair as (right, up) angles in the camera's frame
+pub fn angular_offset(camera: &Transform, point: Vec3) -> Vec2 {
+    let local = camera.rotation.inverse() * (point - camera.translation);
+    Vec2::new(local.x.atan2(-local.z), local.y.atan2(Vec2::new(local.x, local.z).length()))
+}
+
+// Fit per-speed gains so each speed band's flicks would have landed on target.
+// Flicks are binned by peak speed; in each bin the effective sensitivity is scaled by 1 / (1 + overshoot),
+// weighting clean flicks (short correction) above ones whose click error was already corrected by hand.
+// ADS flicks are left out: they turn at the ADS scale, which the hipfire curve doesn't set.
+pub fn fit_curve(samples: &[FlickSample], base_radians_per_count: f32) -> Option<LookupTable> {
+    let mut samples: Vec<&FlickSample> = samples.iter().filter(|s| s.fittable()).collect();
+    let bins = (samples.len() / MIN_SAMPLES_PER_BIN).min(MAX_BINS);
+    if bins == 0 {
+        return None;
+    }
+    samples.sort_by(|a, b| a.peak_speed.total_cmp(&b.peak_speed));
+
+    let points = (0..bins).map(|bin| {
+        let bin_samples = &samples[bin * samples.len() / bins..(bin + 1) * samples.len() / bins];
+        let (weighted, total_weight) = bin_samples.iter().fold((0.0, 0.0), |(sum, total), sample| {
+            let weight = 1.0 / (1.0 + sample.correction_time.max(0.0) / CORRECTION_TIME_SCALE);
+            let overshoot = sample.overshoot().clamp(-MAX_OVERSHOOT_CORRECTION, MAX_OVERSHOOT_CORRECTION);
+            (sum + weight * sample.radians_per_count / (1.0 + overshoot), total + weight)
+        });
+        let speed = bin_samples[bin_samples.len() / 2].peak_speed;
+        [speed, weighted / total_weight / base_radians_per_count]
+    }).collect::<Vec<_>>();
+
+    // Bins can share a median speed when many flicks peak at the same rate; keep the table strictly increasing
+    let mut table = LookupTable::default();
+    for point in points {
+        match table.points.last_mut() {
+            Some(last) if point[0] <= last[0] => last[1] = (last[1] + point[1]) / 2.0,
+            _ => table.points.push(point),
+        }
+    }
+    Some(table)
+}
+
+// Press F between runs to fit a curve from the recorded flicks and save it to the profile
+pub fn fit_curve_on_key(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    scenario_state: Res<ScenarioState>,
+    shot_log: Res<ShotLog>,
+    mut profile: ResMut<Profile>,
+) {
+    if !keyboard.just_pressed(profile.keybinds.fit_curve) || scenario_state.has_started {
+        return;
+    }
+
+    let used: Vec<&FlickSample> = shot_log.flicks.iter().filter(|flick| flick.fittable()).collect();
+    let Some(table) = fit_curve(&shot_log.flicks, profile.radians_per_count()) else {
+        println!("Not enough flicks to fit a curve ({} usable of {} recorded, need {})", used.len(), shot_log.flicks.len(),
+                 MIN_SAMPLES_PER_BIN);
+        return;
+    };
+
+    let count = used.len() as f32;
+    let mean_error = used.iter().map(|flick| flick.angular_error()).sum::<f32>() / count;
+    let hit_rate = used.iter().filter(|flick| flick.hit).count() as f32 / count;
+    println!("Fitted curve from {} of {} flicks, ADS left out (mean click error {:.2} deg, {:.0}% hits):",
+             used.len(), shot_log.flicks.len(), mean_error.to_degrees(), hit_rate * 100.0);
+    for [speed, gain] in &table.points {
+        println!("  {:.2} counts/ms -> gain {:.3}", speed, gain);
+    }
+
+    profile.curve = CurveModel::LookupTable(table);
+    match profile.save() {
+        Ok(()) => println!("Saved fitted curve to profile '{}'", profile.name),
+        Err(err) => eprintln!("Failed to save profile: {}", err),
+    }
+}
+
+// Mean primary endpoint error and number of segmented flicks
+pub fn endpoint_bias<'a>(flicks: impl IntoIterator<Item = &'a FlickSegmentation>) -> Option<(f32, usize)> {
+    let errors: Vec<f32> = flicks.into_iter().map(|flick| flick.endpoint_error).collect();
+    (!errors.is_empty()).then(|| (errors.iter().sum::<f32>() / errors.len() as f32, errors.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A flick at `speed` with the linear base sensitivity, overshooting by `overshoot` of its amplitude
+    fn flick(speed: f32, base: f32, overshoot: f32) -> FlickSample {
+        let amplitude = 0.3;
+        FlickSample {
+            peak_speed: speed,
+            radians_per_count: base,
+            amplitude,
+            direction: Vec2::X,
+            error: Vec2::new(-overshoot * amplitude, 0.0),
+            correction_time: 0.0,
+            hit: true,
+            aiming_down_sights: false,
+            segmentation: None,
+        }
+    }
+
+    // Bell-shaped movements along +x of `distances` radians each, separated by pauses, one event per millisecond
+    fn path(distances: &[f32]) -> Vec<PathPoint> {
+        const EVENTS: usize = 20;
+        let bell: Vec<f32> = (1..=EVENTS).map(|i| (std::f32::consts::PI * i as f32 / (EVENTS + 1) as f32).sin().powi(2)).collect();
+        let total: f32 = bell.iter().sum();
+        let mut points = Vec::new();
+        let mut position = 0.0;
+        for &distance in distances {
+            for step in bell.iter().map(|v| v / total * distance).chain([0.0; 10]) {
+                position += step;
+                points.push(PathPoint { time: points.len() as f32 * 0.001, position: Vec2::new(position, 0.0),
+                                        speed: step.abs() * 1000.0, counts_per_ms: 0.0, radians_per_count: 0.0,
+                                        aiming_down_sights: false });
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn segment_splits_overshoot_and_correction() {
+        let goal = Vec2::new(0.2, 0.0);
+        let flick = segment_flick(&path(&[0.26, -0.06]), Vec2::ZERO, goal).unwrap();
+        assert!((flick.endpoint_error - 0.3).abs() < 1e-3, "{}", flick.endpoint_error);
+        assert_eq!(flick.corrections, 1);
+        assert!(flick.primary_time > 0.015 && flick.primary_time < 0.025, "{}", flick.primary_time);
+        assert!(flick.settle_time > 0.02, "{}", flick.settle_time);
+
+        let flick = segment_flick(&path(&[0.15, 0.03, 0.02]), Vec2::ZERO, goal).unwrap();
+        assert!((flick.endpoint_error + 0.25).abs() < 1e-3, "{}", flick.endpoint_error);
+        assert_eq!(flick.corrections, 2);
+
+        let flick = segment_flick(&path(&[0.2]), Vec2::ZERO, goal).unwrap();
+        assert!(flick.endpoint_error.abs() < 1e-3);
+        assert_eq!((flick.corrections, flick.settle_time), (0, 0.0));
+    }
+
+    #[test]
+    fn segment_ignores_tremor_and_needs_a_real_flick() {
+        // Below a tenth of the primary's peak speed a movement isn't a correction
+        let flick = segment_flick(&path(&[0.22, -0.02]), Vec2::ZERO, Vec2::new(0.2, 0.0)).unwrap();
+        assert_eq!(flick.corrections, 0);
+
+        assert!(segment_flick(&path(&[0.2])[..2], Vec2::ZERO, Vec2::new(0.2, 0.0)).is_none());
+        assert!(segment_flick(&path(&[0.001]), Vec2::ZERO, Vec2::new(0.001, 0.0)).is_none());
+    }
+
+    #[test]
+    fn fit_recovers_synthetic_curve() {
+        // Sensitivity x% above what a speed band needs overshoots by x%
+        let base = 0.001;
+        let ideal_gain = |speed: f32| 1.0 + 0.02 * speed;
+        let samples: Vec<FlickSample> = (1..=30).map(|i| i as f32)
+            .map(|speed| flick(speed, base, 1.0 / ideal_gain(speed) - 1.0))
+            .collect();
+
+        let table = fit_curve(&samples, base).unwrap();
+        assert_eq!(table.points.len(), MAX_BINS);
+        for [speed, gain] in &table.points {
+            assert!((gain - ideal_gain(*speed)).abs() < 1e-3, "gain {} at {}", gain, speed);
+        }
+    }
+
+    #[test]
+    fn fit_follows_a_non_linear_curve() {
+        // S-shaped from 0.8 to 1.6, flat at low and high speeds and steepest around 15 counts/ms
+        let base = 0.001;
+        let ideal_gain = |speed: f32| 0.8 + 0.8 / (1.0 + (-(speed - 15.0) / 4.0).exp());
+        let samples: Vec<FlickSample> = (1..=60).map(|i| i as f32 * 0.5)
+            .map(|speed| flick(speed, base, 1.0 / ideal_gain(speed) - 1.0))
+            .collect();
+
+        let table = fit_curve(&samples, base).unwrap();
+        assert_eq!(table.points.len(), MAX_BINS);
+        for [speed, gain] in &table.points {
+            assert!((gain - ideal_gain(*speed)).abs() < 0.02 * ideal_gain(*speed), "gain {} at {}", gain, speed);
+        }
+        let slopes: Vec<f32> = table.points.windows(2).map(|pair| (pair[1][1] - pair[0][1]) / (pair[1][0] - pair[0][0])).collect();
+        assert!(slopes[slopes.len() / 2] > 2.0 * slopes[0].max(slopes[slopes.len() - 1]), "{:?}", slopes);
+    }
+
+    #[test]
+    fn fit_ignores_ads_flicks_and_needs_enough_samples() {
+        let base = 0.001;
+        let mut samples: Vec<FlickSample> = (1..=10).map(|i| flick(i as f32, base, 0.0)).collect();
+        samples.extend((1..=10).map(|i| FlickSample { aiming_down_sights: true, ..flick(i as f32, base, 0.4) }));
+        let table = fit_curve(&samples, base).unwrap();
+        assert!(table.points.iter().all(|&[_, gain]| (gain - 1.0).abs() < 1e-5));
+
+        assert!(fit_curve(&samples[..MIN_SAMPLES_PER_BIN - 1], base).is_none());
+    }
+}
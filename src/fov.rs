@@ -0,0 +1,127 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_fps_controller::controller::RenderPlayer;
+use serde::{Deserialize, Serialize};
+
+use crate::profile::Profile;
+
+// Which window axis the profile's FOV values are measured along
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FovAxis {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+// How sensitivity scales when the FOV differs from the one the cm/360 was tuned at
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SensMatch {
+    // Same cm/360 at every FOV
+    #[default]
+    Distance360,
+    // Same mouse distance to reach a point `coefficient` of the way to the screen's horizontal edge
+    // (0.5625 at 16:9 is the vertical edge, 0.75 and 1.0 are common choices)
+    MonitorDistance { coefficient: f32 },
+    // Same mouse distance per on-screen pixel near the crosshair (the 0% monitor distance limit)
+    FocalLength,
+}
+
+impl SensMatch {
+    // Factor for radians per count when going from one horizontal FOV to another
+    pub fn scale(&self, from_horizontal: f32, to_horizontal: f32) -> f32 {
+        match *self {
+            Self::Distance360 => 1.0,
+            Self::MonitorDistance { coefficient } if coefficient > 0.0 =>
+                (coefficient * (to_horizontal / 2.0).tan()).atan() / (coefficient * (from_horizontal / 2.0).tan()).atan(),
+            Self::MonitorDistance { .. } | Self::FocalLength => (to_horizontal / 2.0).tan() / (from_horizontal / 2.0).tan(),
+        }
+    }
+}
+
+// Vertical FOV in radians for a FOV in degrees along `axis` in a window with this aspect ratio
+pub fn vertical_fov(fov: f32, axis: FovAxis, aspect: f32) -> f32 {
+    match axis {
+        FovAxis::Vertical => fov.to_radians(),
+        FovAxis::Horizontal => 2.0 * ((fov.to_radians() / 2.0).tan() / aspect).atan(),
+    }
+}
+
+pub fn horizontal_fov(vertical: f32, aspect: f32) -> f32 {
+    2.0 * ((vertical / 2.0).tan() * aspect).atan()
+}
+
+// Current camera FOV and the sensitivity factor that matches it to the profile's game FOV
+#[derive(Resource)]
+pub struct CameraView {
+    pub vertical_fov: f32,
+    pub sensitivity_scale: f32,
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        Self { vertical_fov: Profile::default().camera_fov.to_radians(), sensitivity_scale: 1.0 }
+    }
+}
+
+// Recompute the camera FOV and sensitivity scale from the window aspect, profile and ADS (right mouse) state
+pub fn update_camera_view(
+    profile: Res<Profile>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut view: ResMut<CameraView>,
+    mut projection_query: Query<&mut Projection, With<RenderPlayer>>,
+) {
+    let Ok(window) = window_query.get_single() else { return };
+    let aspect = window.width() / window.height().max(1.0);
+
+    let aiming_down_sights = profile.ads_fov.is_some() && buttons.pressed(MouseButton::Right);
+    let fov = if aiming_down_sights { profile.ads_fov.unwrap_or(profile.camera_fov) } else { profile.camera_fov };
+    let vertical = vertical_fov(fov, profile.fov_axis, aspect);
+
+    // The cm/360 was tuned at the game's own hipfire FOV, shown in this window
+    let game = profile.game();
+    let game_vertical = game.vertical_fov(profile.game_fov.unwrap_or(game.default_fov)).to_radians();
+    let scale = profile.sens_match.scale(horizontal_fov(game_vertical, aspect), horizontal_fov(vertical, aspect));
+
+    *view = CameraView { vertical_fov: vertical, sensitivity_scale: scale };
+    for mut projection in &mut projection_query {
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = vertical;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASPECT_16_9: f32 = 16.0 / 9.0;
+
+    #[test]
+    fn hor_plus_widens_vertical_fov_by_aspect() {
+        // 90 degrees vertical at 16:9 is 121.28 horizontal, and back
+        let horizontal = horizontal_fov(90f32.to_radians(), ASPECT_16_9);
+        assert!((horizontal.to_degrees() - 121.28).abs() < 0.01);
+        assert!((vertical_fov(horizontal.to_degrees(), FovAxis::Horizontal, ASPECT_16_9) - 90f32.to_radians()).abs() < 1e-5);
+        assert_eq!(vertical_fov(90.0, FovAxis::Vertical, ASPECT_16_9), 90f32.to_radians());
+    }
+
+    #[test]
+    fn sens_match_scales_against_known_values() {
+        let (from, to) = (103f32.to_radians(), 60f32.to_radians());
+        assert_eq!(SensMatch::Distance360.scale(from, to), 1.0);
+
+        // Focal length: tan(30) / tan(51.5)
+        let focal = SensMatch::FocalLength.scale(from, to);
+        assert!((focal - 0.4592).abs() < 1e-4, "{}", focal);
+        assert_eq!(SensMatch::MonitorDistance { coefficient: 0.0 }.scale(from, to), focal);
+        // 0% monitor distance is the focal length limit
+        assert!((SensMatch::MonitorDistance { coefficient: 1e-4 }.scale(from, to) - focal).abs() < 1e-4);
+
+        // 100% monitor distance matches the screen edge: half the FOV angles
+        let edge = SensMatch::MonitorDistance { coefficient: 1.0 }.scale(from, to);
+        assert!((edge - 30.0 / 51.5).abs() < 1e-5, "{}", edge);
+        assert_eq!(SensMatch::MonitorDistance { coefficient: 0.75 }.scale(from, from), 1.0);
+    }
+}
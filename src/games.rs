@@ -0,0 +1,134 @@
+use std::fmt;
+
+use crate::fov::{self, FovAxis};
+
+// Which axis a game's FOV setting measures, and at what aspect ratio the value is defined (the profile's own FOV
+// values use fov::FovAxis, measured in the trainer's window)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FovConvention {
+    Horizontal { aspect: f32 },
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Game {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub yaw: f32,         // Degrees turned per mouse count at in-game sensitivity 1
+    pub fov_convention: FovConvention,
+    pub default_fov: f32, // Degrees, measured as described by fov_convention
+}
+
+const ASPECT_4_3: f32 = 4.0 / 3.0;
+const ASPECT_16_9: f32 = 16.0 / 9.0;
+
+pub const GAMES: &[Game] = &[
+    Game { id: "valorant", name: "Valorant", yaw: 0.07, fov_convention: FovConvention::Horizontal { aspect: ASPECT_16_9 }, default_fov: 103.0 },
+    Game { id: "cs2", name: "Counter-Strike 2", yaw: 0.022, fov_convention: FovConvention::Horizontal { aspect: ASPECT_4_3 }, default_fov: 90.0 },
+    Game { id: "apex", name: "Apex Legends", yaw: 0.022, fov_convention: FovConvention::Horizontal { aspect: ASPECT_4_3 }, default_fov: 90.0 },
+    Game { id: "overwatch2", name: "Overwatch 2", yaw: 0.0066, fov_convention: FovConvention::Horizontal { aspect: ASPECT_16_9 }, default_fov: 103.0 },
+    Game { id: "fortnite", name: "Fortnite", yaw: 0.005555, fov_convention: FovConvention::Horizontal { aspect: ASPECT_16_9 }, default_fov: 80.0 },
+    Game { id: "cod", name: "Call of Duty (MW/Warzone)", yaw: 0.0066, fov_convention: FovConvention::Horizontal { aspect: ASPECT_16_9 }, default_fov: 80.0 },
+    Game { id: "r6siege", name: "Rainbow Six Siege", yaw: 0.00572958, fov_convention: FovConvention::Vertical, default_fov: 60.0 },
+    Game { id: "titanfall2", name: "Titanfall 2", yaw: 0.022, fov_convention: FovConvention::Horizontal { aspect: ASPECT_4_3 }, default_fov: 90.0 },
+];
+
+pub fn find(id: &str) -> Option<&'static Game> {
+    GAMES.iter().find(|game| game.id.eq_ignore_ascii_case(id))
+}
+
+impl Game {
+    // In-game sensitivity that gives this cm/360 at this DPI
+    pub fn sensitivity(&self, cm_per_360: f32, dpi: f32) -> f32 {
+        360.0 * 2.54 / (self.yaw * dpi * cm_per_360)
+    }
+
+    // cm/360 produced by an in-game sensitivity at this DPI
+    pub fn cm_per_360(&self, sensitivity: f32, dpi: f32) -> f32 {
+        360.0 * 2.54 / (self.yaw * dpi * sensitivity)
+    }
+
+    // Convert a FOV in this game's convention to a vertical FOV in degrees (the trainer's camera convention)
+    pub fn vertical_fov(&self, fov: f32) -> f32 {
+        match self.fov_convention {
+            FovConvention::Vertical => fov,
+            FovConvention::Horizontal { aspect } => fov::vertical_fov(fov, FovAxis::Horizontal, aspect).to_degrees(),
+        }
+    }
+}
+
+impl fmt::Display for FovConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Horizontal { aspect } if (aspect - ASPECT_4_3).abs() < 0.01 => write!(f, "horizontal at 4:3"),
+            Self::Horizontal { aspect } if (aspect - ASPECT_16_9).abs() < 0.01 => write!(f, "horizontal at 16:9"),
+            Self::Horizontal { aspect } => write!(f, "horizontal at {:.2}:1", aspect),
+            Self::Vertical => write!(f, "vertical"),
+        }
+    }
+}
+
+// Handle the game conversion command-line flags; returns true if one was given
+pub fn run_cli(cm_per_360: f32, dpi: f32, arg_value: impl Fn(&str) -> Option<String>) -> bool {
+    if std::env::args().any(|arg| arg == "--list-games") {
+        for game in GAMES {
+            println!("{:<12} {:<26} yaw {:<8} FOV {} ({}) -> {:.1} vertical",
+                     game.id, game.name, game.yaw, game.default_fov, game.fov_convention, game.vertical_fov(game.default_fov));
+        }
+        return true;
+    }
+
+    // Profile cm/360 to each game's sensitivity, or just the requested game
+    if let Some(id) = arg_value("--to-game") {
+        let games: Vec<&Game> = if id == "all" { GAMES.iter().collect() } else { find(&id).into_iter().collect() };
+        if games.is_empty() {
+            eprintln!("Unknown game '{}', see --list-games", id);
+        }
+        for game in games {
+            println!("{}: {:.4} ({:.1} cm/360 @ {} DPI)", game.name, game.sensitivity(cm_per_360, dpi), cm_per_360, dpi as i32);
+        }
+        return true;
+    }
+
+    // A game's sensitivity to cm/360 at the profile's DPI, as the two values following the flag
+    if std::env::args().any(|arg| arg == "--from-game") {
+        let mut args = std::env::args().skip_while(|arg| arg != "--from-game").skip(1);
+        let (id, sensitivity) = (args.next(), args.next().and_then(|value| value.parse::<f32>().ok()));
+        match (id.as_deref().map(|id| (id, find(id))), sensitivity) {
+            (Some((_, Some(game))), Some(sensitivity)) if sensitivity > 0.0 => println!("{} {} @ {} DPI = {:.2} cm/360",
+                game.name, sensitivity, dpi as i32, game.cm_per_360(sensitivity, dpi)),
+            (Some((id, None)), _) => eprintln!("Unknown game '{}', see --list-games", id),
+            _ => eprintln!("Usage: --from-game <game> <sensitivity>"),
+        }
+        return true;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_fov_converts_to_vertical() {
+        // CS2's 90 degrees at 4:3 is 73.74 vertical, Valorant's 103 at 16:9 is 70.53
+        assert!((find("cs2").unwrap().vertical_fov(90.0) - 73.74).abs() < 0.01);
+        assert!((find("valorant").unwrap().vertical_fov(103.0) - 70.53).abs() < 0.01);
+        assert_eq!(find("r6siege").unwrap().vertical_fov(60.0), 60.0);
+    }
+
+    #[test]
+    fn sensitivity_and_cm_per_360_invert_each_other() {
+        // CS2 at sensitivity 1 and 800 DPI: 360 degrees / 0.022 per count = 16364 counts, 20.45 inches
+        let cs2 = find("cs2").unwrap();
+        assert!((cs2.cm_per_360(1.0, 800.0) - 51.95).abs() < 0.01);
+        assert!((cs2.sensitivity(51.9545, 800.0) - 1.0).abs() < 1e-4);
+        for game in GAMES {
+            for (cm_per_360, dpi) in [(25.0, 1600.0), (40.0, 800.0), (62.3, 400.0)] {
+                let sensitivity = game.sensitivity(cm_per_360, dpi);
+                assert!((game.cm_per_360(sensitivity, dpi) - cm_per_360).abs() < 1e-3, "{} at {} cm", game.id, cm_per_360);
+            }
+        }
+    }
+}
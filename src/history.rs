@@ -0,0 +1,242 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, io::{self, BufRead, BufReader, Write}, ops::RangeInclusive, path::{Path, PathBuf}, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::{aim_profile::AimProfile, calibration::Calibration, fitts, flick, miss, profile::{self, Profile},
+            reaction::{self, TargetReaction}, scoring::{ScoreCard, SessionResults},
+            smoothness::Smoothness, telemetry::MotionRecorder, ScenarioEvent, ScenarioState, ScenarioType};
+
+// Summary metrics of one scenario run, as stored in the history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioMetrics {
+    pub scenario: ScenarioType,
+    pub score: i32,
+    pub hits: u32,
+    pub misses: u32,
+    pub duration: f32,
+    pub accuracy: f32,
+    pub kills_per_second: f32,
+    pub mean_time_to_kill: Option<f32>,
+    pub time_on_target: Option<f32>,
+    pub damage: Option<f32>,
+    pub reaction_latency: Option<f32>,
+    pub acquisition_time: Option<f32>,
+    pub confirmation_delay: Option<f32>,
+    pub throughput: Option<f32>,     // Fitts's law, bits/s
+    pub endpoint_error: Option<f32>, // Mean primary flick endpoint error, fraction of distance
+    pub miss_bias: Option<[f32; 2]>, // Mean miss offset, radians (right, up)
+    pub smoothness: Option<Smoothness>,
+}
+
+impl ScenarioMetrics {
+    pub fn from_card(card: &ScoreCard) -> Option<Self> {
+        let tracking = card.is_tracking();
+        Some(Self {
+            scenario: card.scenario?,
+            score: card.score(),
+            hits: card.hits,
+            misses: card.misses,
+            duration: card.duration,
+            accuracy: card.accuracy(),
+            kills_per_second: card.kills_per_second(),
+            mean_time_to_kill: card.mean_time_to_kill(),
+            time_on_target: tracking.then(|| card.time_on_target()),
+            damage: tracking.then_some(card.damage),
+            reaction_latency: reaction::mean(&card.reactions, TargetReaction::reaction_latency),
+            acquisition_time: reaction::mean(&card.reactions, TargetReaction::acquisition_time),
+            confirmation_delay: reaction::mean(&card.reactions, TargetReaction::confirmation_delay),
+            throughput: fitts::fit(&card.fitts).map(|fit| fit.throughput),
+            endpoint_error: flick::endpoint_bias(&card.flicks).map(|(bias, _)| bias),
+            miss_bias: miss::error_ellipse(&card.missed_shots).map(|ellipse| ellipse.mean.to_array()),
+            smoothness: card.smoothness,
+        })
+    }
+}
+
+// One completed scenario sequence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub timestamp: u64, // Unix seconds
+    pub app_version: String,
+    pub session: String, // Telemetry session directory name
+    pub profile_name: String,
+    pub profile: Profile, // Settings the sequence was played at (a calibration block's candidate cm/360)
+    pub calibration_block: bool,
+    #[serde(default)]
+    pub partial: bool, // Saved from the journal of a sequence that never completed
+    #[serde(default)]
+    pub seed: Option<u64>, // Target seed, for replaying the sequence with `--seed`
+    pub scenarios: Vec<ScenarioMetrics>,
+    pub aim_profile: AimProfile,
+}
+
+impl SessionRecord {
+    pub fn new(cards: &[ScoreCard], profile: &Profile, session: &str, calibration_block: bool, seed: Option<u64>) -> Self {
+        Self {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            session: session.to_string(),
+            profile_name: profile.name.clone(),
+            profile: profile.clone(),
+            calibration_block,
+            partial: false,
+            seed,
+            scenarios: cards.iter().filter_map(ScenarioMetrics::from_card).collect(),
+            aim_profile: AimProfile::from_cards(cards),
+        }
+    }
+}
+
+pub fn path() -> PathBuf {
+    profile::data_dir().join("history.jsonl")
+}
+
+pub fn append(record: &SessionRecord) -> io::Result<()> {
+    append_all(std::slice::from_ref(record))
+}
+
+pub fn append_all(records: &[SessionRecord]) -> io::Result<()> {
+    fs::create_dir_all(profile::data_dir())?;
+    let mut file = io::BufWriter::new(fs::OpenOptions::new().create(true).append(true).open(path())?);
+    for record in records {
+        let line = serde_json::to_string(record).map_err(io::Error::other)?;
+        writeln!(file, "{}", line)?;
+    }
+    file.flush()
+}
+
+// Drop the records `keep` rejects and return how many went. Lines that don't parse (e.g. runs of a scenario file
+// that was since removed) are written back untouched, so they come back if the scenario does.
+pub fn retain(keep: impl Fn(&SessionRecord) -> bool) -> io::Result<usize> {
+    retain_in(&path(), keep)
+}
+
+fn retain_in(path: &Path, keep: impl Fn(&SessionRecord) -> bool) -> io::Result<usize> {
+    let Ok(contents) = fs::read_to_string(path) else { return Ok(0) };
+    let mut removed = 0;
+    let temp = path.with_extension("jsonl.tmp");
+    let mut file = io::BufWriter::new(fs::File::create(&temp)?);
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        if serde_json::from_str::<SessionRecord>(line).is_ok_and(|record| !keep(&record)) {
+            removed += 1;
+        } else {
+            writeln!(file, "{}", line)?;
+        }
+    }
+    file.flush()?;
+    drop(file);
+    fs::rename(temp, path)?;
+    Ok(removed)
+}
+
+// Every readable record, oldest first; unreadable lines are reported and skipped
+pub fn load() -> Vec<SessionRecord> {
+    let Ok(file) = fs::File::open(path()) else { return Vec::new() };
+    let mut records: Vec<SessionRecord> = BufReader::new(file).lines().map_while(Result::ok).enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(i, line)| serde_json::from_str(&line)
+            .map_err(|err| eprintln!("Skipping history line {}: {}", i + 1, err)).ok())
+        .collect();
+    // Imported records are appended after local ones that may be newer
+    records.sort_by_key(|record| record.timestamp);
+    records
+}
+
+// Runs of one scenario, oldest first
+pub fn for_scenario(records: &[SessionRecord], scenario: ScenarioType) -> Vec<(&SessionRecord, &ScenarioMetrics)> {
+    records.iter()
+        .flat_map(|record| record.scenarios.iter().map(move |metrics| (record, metrics)))
+        .filter(|(_, metrics)| metrics.scenario == scenario)
+        .collect()
+}
+
+// Sequences played at a cm/360 within the range
+pub fn with_sensitivity(records: &[SessionRecord], cm_per_360: RangeInclusive<f32>) -> Vec<&SessionRecord> {
+    records.iter().filter(|record| cm_per_360.contains(&record.profile.sensitivity_cm_per_360)).collect()
+}
+
+// Print the history for `--history <scenario|all>`, optionally only around `--history-cm <cm/360>` (+-10%)
+pub fn run_cli(arg_value: impl Fn(&str) -> Option<String>) -> bool {
+    let Some(filter) = arg_value("--history") else { return false };
+    let records = load();
+    let records: Vec<SessionRecord> = match arg_value("--history-cm").and_then(|value| value.parse::<f32>().ok()) {
+        Some(cm) => with_sensitivity(&records, cm * 0.9..=cm * 1.1).into_iter().cloned().collect(),
+        None => records,
+    };
+
+    let scenarios: Vec<ScenarioType> = if filter == "all" {
+        ScenarioType::all()
+    } else {
+        ScenarioType::find(&filter).into_iter().collect()
+    };
+    if scenarios.is_empty() {
+        eprintln!("Unknown scenario '{}'", filter);
+    }
+    for scenario in scenarios {
+        println!("{:?}:", scenario);
+        for (record, metrics) in for_scenario(&records, scenario) {
+            println!("  {} {:>6.1} cm/360 score {:>4} acc {:>3.0}%{}{}", record.timestamp,
+                     record.profile.sensitivity_cm_per_360, metrics.score, metrics.accuracy * 100.0,
+                     record.seed.map_or(String::new(), |seed| format!(" seed {}", seed)),
+                     if record.calibration_block { " (calibration)" } else { "" });
+        }
+    }
+    true
+}
+
+// Append every completed sequence, calibration blocks included, to the history
+pub fn save_history(mut events: EventReader<ScenarioEvent>, results: Res<SessionResults>, profile: Res<Profile>,
+                    calibration: Res<Calibration>, recorder: Res<MotionRecorder>, scenario_state: Res<ScenarioState>) {
+    if !events.read().any(|event| matches!(event, ScenarioEvent::Completed)) {
+        return;
+    }
+    let record = SessionRecord::new(&results.completed, &profile, recorder.session(), calibration.is_running(),
+                                    Some(scenario_state.seed));
+    match append(&record) {
+        Ok(()) => println!("Saved session to {}", path().display()),
+        Err(err) => eprintln!("Failed to save session history: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: u64, cm_per_360: f32, scenarios: &[(&str, u32)]) -> SessionRecord {
+        let cards: Vec<ScoreCard> = scenarios.iter()
+            .map(|&(name, hits)| ScoreCard { hits, ..ScoreCard::new(ScenarioType::find(name)) })
+            .collect();
+        let profile = Profile { sensitivity_cm_per_360: cm_per_360, ..default() };
+        SessionRecord { timestamp, ..SessionRecord::new(&cards, &profile, &timestamp.to_string(), false, None) }
+    }
+
+    #[test]
+    fn for_scenario_lists_runs_across_records() {
+        let records = [record(1, 30.0, &[("StaticClicking", 10), ("PreciseTracking", 0)]),
+                       record(2, 35.0, &[("StaticClicking", 12), ("StaticClicking", 14)])];
+        let runs = for_scenario(&records, ScenarioType::find("StaticClicking").unwrap());
+        let hits: Vec<(u64, u32)> = runs.iter().map(|(record, metrics)| (record.timestamp, metrics.hits)).collect();
+        assert_eq!(hits, [(1, 10), (2, 12), (2, 14)]);
+        assert!(for_scenario(&records, ScenarioType::find("SpeedSwitching").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn with_sensitivity_filters_inclusively() {
+        let records = [record(1, 27.0, &[]), record(2, 30.0, &[]), record(3, 33.0, &[]), record(4, 40.0, &[])];
+        let matching: Vec<u64> = with_sensitivity(&records, 27.0..=33.0).iter().map(|record| record.timestamp).collect();
+        assert_eq!(matching, [1, 2, 3]);
+    }
+
+    #[test]
+    fn retain_drops_rejected_records_and_keeps_unreadable_lines() {
+        let path = std::env::temp_dir().join(format!("neurocurve_history_{}.jsonl", std::process::id()));
+        let lines = [serde_json::to_string(&record(1, 30.0, &[])).unwrap(), "{\"not\": \"a record\"}".to_string(),
+                     serde_json::to_string(&record(2, 40.0, &[])).unwrap()];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        assert_eq!(retain_in(&path, |record| record.profile.sensitivity_cm_per_360 < 35.0).unwrap(), 1);
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(contents.lines().collect::<Vec<_>>(), [lines[0].as_str(), lines[1].as_str()]);
+    }
+}
@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+use crate::{calibration::Calibration, history::{self, SessionRecord}, playlist::{self, ScheduledScenario},
+            profile::{self, Keybinds, Profile}, resume_scenario_sequence, scoring::{ScoreCard, SessionResults},
+            telemetry::MotionRecorder, ScenarioEvent, ScenarioState, Target};
+
+// Progress through a scenario sequence, rewritten as each scenario ends so a crash or closed window loses at most
+// the scenario in progress. The scenario telemetry itself is already in the session's telemetry directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Journal {
+    pub session: String, // Telemetry session directory name
+    pub profile_name: String,
+    pub profile: Profile,
+    pub playlist: String,
+    pub scenarios: Vec<ScheduledScenario>, // As scheduled, so a shuffled playlist resumes in the same order
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub next_index: usize,
+    pub completed: Vec<ScoreCard>,
+}
+
+// A journal left behind by the previous run, until the user resumes, saves or discards it
+#[derive(Resource, Default)]
+pub struct PendingJournal(pub Option<Journal>);
+
+// The user's own profile while a resumed sequence plays at the journal's settings. Put back when the sequence
+// completes, so nothing saves the journal's snapshot over it.
+#[derive(Resource, Default)]
+pub struct ProfileBeforeResume(pub Option<Profile>);
+
+#[derive(Component)]
+pub struct JournalPrompt;
+
+pub fn path() -> PathBuf {
+    profile::data_dir().join("journal.json")
+}
+
+// Written to a temporary file and renamed over the old journal, so a crash mid-write keeps the previous one
+fn write(journal: &Journal) -> io::Result<()> {
+    fs::create_dir_all(profile::data_dir())?;
+    let temp = path().with_extension("json.tmp");
+    fs::write(&temp, serde_json::to_string(journal).map_err(io::Error::other)?)?;
+    fs::rename(temp, path())
+}
+
+pub fn load() -> Option<Journal> {
+    let contents = fs::read_to_string(path()).ok()?;
+    let mut journal: Journal = serde_json::from_str(&contents)
+        .map_err(|err| eprintln!("Ignoring unreadable session journal: {}", err)).ok()?;
+    journal.profile.name = journal.profile_name.clone();
+    Some(journal)
+}
+
+fn remove() {
+    if let Err(err) = fs::remove_file(path()) {
+        if err.kind() != io::ErrorKind::NotFound {
+            eprintln!("Failed to remove session journal: {}", err);
+        }
+    }
+}
+
+impl Journal {
+    fn prompt(&self, keybinds: &Keybinds) -> String {
+        let next = self.scenarios.get(self.next_index).map_or("-".to_string(), |run| format!("{:?}", run.scenario));
+        format!("Unfinished session from a previous run ({} of {} scenarios done, profile '{}')\n\
+                 {}: resume at {} | {}: save the partial session to history | {}: discard",
+                self.next_index, self.scenarios.len(), self.profile_name, profile::key_name(keybinds.resume_session),
+                next, profile::key_name(keybinds.save_partial_session), profile::key_name(keybinds.discard_session))
+    }
+}
+
+pub fn spawn_prompt(mut commands: Commands, pending: Res<PendingJournal>, profile: Res<Profile>) {
+    let Some(journal) = &pending.0 else { return };
+    commands.spawn((Text::new(journal.prompt(&profile.keybinds)),
+                   Node { position_type: PositionType::Absolute, top: Val::Px(110.), left: Val::Px(15.), ..default() },
+                   JournalPrompt));
+}
+
+// Journal every scenario end of a normal sequence and drop the journal once the sequence completes.
+// Calibration blocks are not journaled since their search can't be resumed.
+pub fn write_journal(mut events: EventReader<ScenarioEvent>, results: Res<SessionResults>, scenario_state: Res<ScenarioState>,
+                     profile: Res<Profile>, calibration: Res<Calibration>, recorder: Res<MotionRecorder>) {
+    for event in events.read() {
+        match *event {
+            ScenarioEvent::Ended { index, .. } if !calibration.is_running() => {
+                let journal = Journal {
+                    session: recorder.session().to_string(),
+                    profile_name: profile.name.clone(),
+                    profile: profile.clone(),
+                    playlist: scenario_state.playlist.name.clone(),
+                    scenarios: scenario_state.scenarios.clone(),
+                    seed: Some(scenario_state.seed),
+                    next_index: index + 1,
+                    completed: results.completed.clone(),
+                };
+                if let Err(err) = write(&journal) {
+                    eprintln!("Failed to write session journal: {}", err);
+                }
+            },
+            ScenarioEvent::Completed if !calibration.is_running() => remove(),
+            _ => {},
+        }
+    }
+}
+
+// Between runs, the resume key resumes the journaled sequence, the partial-save key files the completed scenarios in
+// the history, and the discard key or starting afresh discards it
+pub fn handle_journal_prompt(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut pending: ResMut<PendingJournal>,
+    mut scenario_state: ResMut<ScenarioState>,
+    calibration: Res<Calibration>,
+    mut results: ResMut<SessionResults>,
+    mut recorder: ResMut<MotionRecorder>,
+    mut profile: ResMut<Profile>,
+    mut before_resume: ResMut<ProfileBeforeResume>,
+    mut commands: Commands,
+    targets: Query<Entity, With<Target>>,
+    prompt: Query<Entity, With<JournalPrompt>>,
+) {
+    if pending.0.is_none() || scenario_state.has_started || calibration.is_running() {
+        return;
+    }
+
+    let keybinds = profile.keybinds.clone();
+    if keyboard.just_pressed(keybinds.resume_session) {
+        // Finish at the settings the sequence started with; the user's profile comes back once it completes
+        let journal = pending.0.take().unwrap();
+        before_resume.0 = Some(std::mem::replace(&mut *profile, journal.profile));
+        recorder.resume_session(&journal.session);
+        println!("Resuming session {} at scenario {} of {}", journal.session, journal.next_index + 1, journal.scenarios.len());
+        resume_scenario_sequence(&mut scenario_state, &mut results, &mut commands, &targets,
+                                 playlist::find_or_default(&journal.playlist), journal.seed, journal.scenarios,
+                                 journal.next_index, journal.completed);
+    } else if keyboard.just_pressed(keybinds.save_partial_session) {
+        let Some(journal) = &pending.0 else { return };
+        let mut record = SessionRecord::new(&journal.completed, &journal.profile, &journal.session, false, journal.seed);
+        record.partial = true;
+        if let Err(err) = history::append(&record) {
+            eprintln!("Failed to save partial session: {}", err);
+            return;
+        }
+        println!("Saved {} completed scenarios of the unfinished session to {}", record.scenarios.len(), history::path().display());
+        pending.0 = None;
+        remove();
+    } else if keyboard.just_pressed(keybinds.discard_session) || keyboard.just_pressed(keybinds.start) {
+        pending.0 = None;
+        println!("Discarded the unfinished session");
+        remove();
+    } else {
+        return;
+    }
+
+    for entity in &prompt {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Put the user's own profile back once a resumed sequence completes and has been recorded
+pub fn restore_profile(mut events: EventReader<ScenarioEvent>, mut before_resume: ResMut<ProfileBeforeResume>,
+                       mut profile: ResMut<Profile>) {
+    if !events.read().any(|event| matches!(event, ScenarioEvent::Completed)) {
+        return;
+    }
+    if let Some(saved) = before_resume.0.take() {
+        *profile = saved;
+    }
+}
@@ -0,0 +1,212 @@
+use std::{fmt, fs, io, path::Path};
+
+use crate::curve::{CurveModel, LookupTable, SensitivityCurve};
+use crate::profile::Profile;
+
+const MAX_POINTS: usize = 64; // libinput's limit for custom acceleration points
+const MAX_SPEED: f32 = 50.0; // Mouse counts/ms covered by the exported points
+const NORMALIZED_DPI: f32 = 1000.0; // libinput normalizes device deltas to 1000 DPI before applying the profile
+
+#[derive(Debug)]
+pub enum LibinputError {
+    Io(io::Error),
+    NotMonotonic { speed: f32 },
+    Parse(String),
+}
+
+impl fmt::Display for LibinputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::NotMonotonic { speed } =>
+                write!(f, "curve output speed drops at {:.2} units/ms, libinput needs it non-decreasing", speed),
+            Self::Parse(reason) => write!(f, "invalid libinput profile: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LibinputError {}
+
+impl From<io::Error> for LibinputError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+// libinput "custom" acceleration: output speed sampled at input speeds 0, step, 2 * step, ..., all in units/ms
+// normalized to 1000 DPI
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomProfile {
+    pub step: f32,
+    pub points: Vec<f32>,
+}
+
+impl CustomProfile {
+    // Sample a curve as output speed = input speed * gain, rejecting curves that slow the pointer down. The curve
+    // takes counts/ms of a `dpi` mouse, which libinput sees as counts/ms * 1000 / dpi.
+    pub fn from_curve(curve: &dyn SensitivityCurve, dpi: f32) -> Result<Self, LibinputError> {
+        let counts_step = MAX_SPEED / (MAX_POINTS - 1) as f32;
+        let step = counts_step * NORMALIZED_DPI / dpi;
+        let points: Vec<f32> = (0..MAX_POINTS).map(|i| i as f32 * step * curve.gain(i as f32 * counts_step)).collect();
+        if let Some(i) = points.windows(2).position(|w| w[1] < w[0]) {
+            return Err(LibinputError::NotMonotonic { speed: (i + 1) as f32 * step });
+        }
+        Ok(Self { step, points })
+    }
+
+    // Gain at each sampled speed, back in counts/ms of a `dpi` mouse; speed 0 borrows the first non-zero sample's gain
+    pub fn to_curve(&self, dpi: f32) -> CurveModel {
+        let mut points: Vec<[f32; 2]> = self.points.iter().enumerate().skip(1)
+            .map(|(i, output)| [i as f32 * self.step * dpi / NORMALIZED_DPI, output / (i as f32 * self.step)])
+            .collect();
+        if let Some(&[_, gain]) = points.first() {
+            points.insert(0, [0.0, gain]);
+        }
+        CurveModel::LookupTable(LookupTable { points })
+    }
+
+    fn points_text(&self) -> String {
+        self.points.iter().map(|point| format!("{:.4}", point)).collect::<Vec<_>>().join(" ")
+    }
+
+    // Profile values followed by config snippets for each environment
+    pub fn to_config(&self, profile_name: &str, dpi: f32) -> String {
+        let points = self.points_text();
+        format!("\
+            # libinput custom acceleration profile for NeuroCurveCalibration profile '{name}'\n\
+            # Speeds are in units/ms normalized to 1000 DPI, made for a {dpi} DPI mouse; set the game to the profile's\n\
+            # cm/360 and leave its own acceleration off.\n\
+            step = {step:.4}\n\
+            points = {points}\n\
+            \n\
+            # --- X11: /etc/X11/xorg.conf.d/50-neurocurve.conf (xf86-input-libinput 1.3+) ---\n\
+            # Section \"InputClass\"\n\
+            #     Identifier \"NeuroCurveCalibration\"\n\
+            #     MatchIsPointer \"yes\"\n\
+            #     Driver \"libinput\"\n\
+            #     Option \"AccelProfile\" \"custom\"\n\
+            #     Option \"AccelStepMotion\" \"{step:.4}\"\n\
+            #     Option \"AccelPointsMotion\" \"{points}\"\n\
+            # EndSection\n\
+            \n\
+            # --- GNOME (Mutter) only offers flat/adaptive profiles; keep the device flat and use the X11 snippet above ---\n\
+            # gsettings set org.gnome.desktop.peripherals.mouse accel-profile 'flat'\n\
+            \n\
+            # --- sway only offers flat/adaptive profiles; keep the device flat and apply the curve under X11 or Hyprland ---\n\
+            # input type:pointer {{\n\
+            #     accel_profile flat\n\
+            #     pointer_accel 0\n\
+            # }}\n\
+            \n\
+            # --- Hyprland: ~/.config/hypr/hyprland.conf ---\n\
+            # input {{\n\
+            #     accel_profile = custom {step:.4} {points}\n\
+            # }}\n",
+            name = profile_name, dpi = dpi, step = self.step, points = points)
+    }
+
+    // Accepts the exported file, an xorg.conf.d section or a Hyprland line
+    pub fn parse(text: &str) -> Result<Self, LibinputError> {
+        let numbers = |value: &str| -> Result<Vec<f32>, LibinputError> {
+            value.trim().trim_matches('"').split_whitespace()
+                .map(|n| n.parse().map_err(|_| LibinputError::Parse(format!("bad number {:?}", n))))
+                .collect()
+        };
+
+        for line in text.lines().map(|line| line.trim_start_matches('#').trim()) {
+            if let Some(values) = line.strip_prefix("accel_profile = custom") {
+                let values = numbers(values)?;
+                if let Some((step, points)) = values.split_first() {
+                    return Self::checked(*step, points.to_vec());
+                }
+            }
+        }
+
+        let find = |keys: &[&str]| text.lines().map(|line| line.trim()).find_map(|line| {
+            keys.iter().find_map(|key| line.strip_prefix(key).map(|rest| rest.trim_start_matches([' ', '=', '"'])))
+        });
+        let step = find(&["step", "Option \"AccelStepMotion\""]).ok_or(LibinputError::Parse("missing step".to_string()))?;
+        let points = find(&["points", "Option \"AccelPointsMotion\""]).ok_or(LibinputError::Parse("missing points".to_string()))?;
+        Self::checked(numbers(step)?.first().copied().unwrap_or_default(), numbers(points)?)
+    }
+
+    fn checked(step: f32, points: Vec<f32>) -> Result<Self, LibinputError> {
+        if step <= 0.0 || !step.is_finite() || points.len() < 2 || points.len() > MAX_POINTS {
+            return Err(LibinputError::Parse(format!("need step > 0 and 2-{} points", MAX_POINTS)));
+        }
+        if let Some(i) = points.windows(2).position(|w| w[1] < w[0]) {
+            return Err(LibinputError::NotMonotonic { speed: (i + 1) as f32 * step });
+        }
+        Ok(Self { step, points })
+    }
+}
+
+pub fn export(profile: &Profile, path: &Path) -> Result<(), LibinputError> {
+    let custom = CustomProfile::from_curve(&profile.curve, profile.mouse_dpi)?;
+    fs::write(path, custom.to_config(&profile.name, profile.mouse_dpi))?;
+    Ok(())
+}
+
+// A curve over counts/ms of a `dpi` mouse, from a profile libinput applies to its normalized speeds
+pub fn import(path: &Path, dpi: f32) -> Result<CurveModel, LibinputError> {
+    let curve = CustomProfile::parse(&fs::read_to_string(path)?)?.to_curve(dpi);
+    curve.validate().map_err(LibinputError::Parse)?;
+    Ok(curve)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Classic;
+
+    #[test]
+    fn config_parses_back_to_the_exported_profile() {
+        let curve = CurveModel::Classic(Classic { offset: 2.0, acceleration: 0.05, exponent: 2.0, cap: 2.0 });
+        let exported = CustomProfile::from_curve(&curve, 1600.0).unwrap();
+        let parsed = CustomProfile::parse(&exported.to_config("test", 1600.0)).unwrap();
+        assert_eq!(parsed.points.len(), MAX_POINTS);
+        assert!((parsed.step - exported.step).abs() < 1e-4);
+        assert!(parsed.points.iter().zip(&exported.points).all(|(a, b)| (a - b).abs() < 1e-4));
+
+        // The gains sampled back out match the curve at every exported speed
+        let CurveModel::LookupTable(table) = parsed.to_curve(1600.0) else { panic!("expected a lookup table") };
+        for &[speed, gain] in &table.points[1..] {
+            assert!((gain - curve.gain(speed)).abs() < 1e-3, "gain {} at {}", gain, speed);
+        }
+        assert!((table.points.last().unwrap()[0] - MAX_SPEED).abs() < 1e-2);
+    }
+
+    #[test]
+    fn speeds_are_normalized_to_1000_dpi() {
+        // 50 counts/ms of an 800 DPI mouse is 62.5 units/ms to libinput
+        let curve = CurveModel::LookupTable(LookupTable { points: vec![[0.0, 1.0], [MAX_SPEED, 2.0]] });
+        let exported = CustomProfile::from_curve(&curve, 800.0).unwrap();
+        assert!((exported.step * (MAX_POINTS - 1) as f32 - 62.5).abs() < 1e-3);
+        for (i, output) in exported.points.iter().enumerate().skip(1) {
+            let speed = i as f32 * exported.step;
+            assert!((output / speed - curve.gain(speed * 0.8)).abs() < 1e-4, "gain at {} units/ms", speed);
+        }
+    }
+
+    #[test]
+    fn parses_hyprland_and_xorg_lines() {
+        let hyprland = CustomProfile::parse("input {\n    accel_profile = custom 0.5 0 0.5 1.5\n}").unwrap();
+        assert_eq!(hyprland, CustomProfile { step: 0.5, points: vec![0.0, 0.5, 1.5] });
+        let xorg = CustomProfile::parse("Option \"AccelStepMotion\" \"0.5\"\nOption \"AccelPointsMotion\" \"0 0.5 1.5\"").unwrap();
+        assert_eq!(xorg, hyprland);
+    }
+
+    #[test]
+    fn rejects_more_than_64_points() {
+        let points = (0..=MAX_POINTS).map(|i| i as f32).map(|p| p.to_string()).collect::<Vec<_>>().join(" ");
+        let text = format!("step = 1.0\npoints = {}", points);
+        assert!(matches!(CustomProfile::parse(&text), Err(LibinputError::Parse(_))));
+    }
+
+    #[test]
+    fn rejects_decreasing_output() {
+        assert!(matches!(CustomProfile::parse("step = 1.0\npoints = 0 2 1"), Err(LibinputError::NotMonotonic { .. })));
+        let slowing = CurveModel::LookupTable(LookupTable { points: vec![[0.0, 1.0], [10.0, 0.1]] });
+        assert!(matches!(CustomProfile::from_curve(&slowing, 1000.0), Err(LibinputError::NotMonotonic { .. })));
+    }
+}
@@ -1,13 +1,54 @@
-use bevy::{prelude::*, render::camera::Exposure, time::{Stopwatch, Timer, TimerMode}, window::CursorGrabMode};
+use bevy::{input::mouse::MouseMotion, prelude::*, render::camera::Exposure, time::{Stopwatch, Timer, TimerMode}, window::CursorGrabMode};
 use bevy_diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy_fps_controller::controller::*;
 use bevy_rapier3d::prelude::*;
 use rand::{distr::Uniform, prelude::*};
-use std::f32::consts::TAU;
+use rand_chacha::ChaCha8Rng;
+use std::f32::consts::FRAC_PI_2;
+
+mod aim_profile;
+mod bundle;
+mod calibration;
+mod columnar;
+mod curve;
+mod definitions;
+mod flick;
+mod fitts;
+mod fov;
+mod games;
+mod history;
+mod journal;
+mod libinput;
+mod miss;
+mod playlist;
+mod profile;
+mod rawaccel;
+mod reaction;
+mod results_screen;
+mod rng;
+mod scenarios;
+mod scoring;
+mod shots;
+mod smoothness;
+mod telemetry;
+
+use calibration::Calibration;
+use curve::SensitivityCurve;
+use flick::FlickTracker;
+use fov::CameraView;
+use journal::{PendingJournal, ProfileBeforeResume};
+use miss::{MissSample, TargetVelocity};
+use playlist::{Playlist, ScheduledScenario};
+use profile::{Crosshair, Profile};
+use results_screen::{ResultsScreen, SessionRecords};
+use rng::{SeededRng, Stream};
+use scenarios::{Movement, MovementPattern, Respawn, ScenarioType};
+use scoring::{ScoreCard, SessionResults};
+use shots::{ShotEvent, ShotLog};
+use smoothness::{PendingSmoothness, TrackingRecorder};
+use telemetry::{MotionRecorder, MotionSample};
 
 // Game constants
-const SENSITIVITY_CM_PER_360: f32 = 10.0;
-const MOUSE_DPI: f32 = 1600.0;
 const ARENA_WIDTH: f32 = 200.0;
 const ARENA_DEPTH: f32 = 200.0;
 const ARENA_HEIGHT: f32 = 50.0;
@@ -18,37 +59,17 @@ const TARGET_SIZE: f32 = 1.5;
 const PLAYER_HEIGHT: f32 = 10.0;
 const PLAYER_RADIUS: f32 = 0.5;
 const CAMERA_HEIGHT_OFFSET: f32 = 4.0;
-// Valorant uses a vertical FOV of 90 degrees (approximately 1.57 radians)
-const CAMERA_FOV: f32 = std::f32::consts::FRAC_PI_2; // 90 degrees in radians
+// Keeps pitch just short of straight up/down, matching the controller's own clamp
+const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.001953125;
 const CENTER_SIZE: f32 = 8.0;
 const GRID_SPACING: f32 = 4.0;
-
-// Scenario constants
-const SCENARIO_DURATION: f32 = 30.0; // Duration of each scenario in seconds
-const SCENARIO_DELAY: f32 = 5.0; // Delay between scenarios in seconds
+// Target movement and refill rate; part of what a seed replays, so changing it changes every recorded path
+const FIXED_HZ: f64 = 64.0;
 
 // Component and resource definitions
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub struct FpsControllerSetup;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum ScenarioType {
-    // Clicking scenarios
-    DynamicClicking,  // Targets move in unpredictable patterns
-    StaticClicking,   // Targets are stationary
-    LinearClicking,   // Targets move in straight lines
-
-    // Tracking scenarios
-    PreciseTracking,  // Slow, precise movements requiring accuracy
-    ReactiveTracking, // Quick, sudden movements requiring fast reactions
-    ControlTracking,  // Smooth, consistent movements requiring control
-
-    // Switching scenarios
-    SpeedSwitching,   // Fast target switching with emphasis on speed
-    EvasiveSwitching, // Targets that try to evade the crosshair
-    StabilitySwitching // Targets that require stability between switches
-}
-
 #[derive(Resource)]
 struct ScenarioState {
     current_type: Option<ScenarioType>,
@@ -57,75 +78,200 @@ struct ScenarioState {
     current_index: usize,
     is_active: bool,
     has_started: bool,
-    scenarios: Vec<ScenarioType>,
+    playlist: &'static Playlist, // Playlist the current sequence was scheduled from
+    scenarios: Vec<ScheduledScenario>,
+    seed: u64,                // Seed the current sequence's targets are drawn from
+    fixed_seed: Option<u64>,  // `--seed`: play every sequence with this seed
 }
 
 impl Default for ScenarioState {
     fn default() -> Self {
+        let playlist = playlist::find_or_default(playlist::DEFAULT_PLAYLIST);
         Self {
             current_type: None,
-            scenario_timer: Timer::from_seconds(SCENARIO_DURATION, TimerMode::Once),
-            delay_timer: Timer::from_seconds(SCENARIO_DELAY, TimerMode::Once),
+            scenario_timer: Timer::default(),
+            delay_timer: Timer::default(),
             current_index: 0,
             is_active: false,
             has_started: false,
-            scenarios: vec![
-                ScenarioType::DynamicClicking,
-                ScenarioType::StaticClicking,
-                ScenarioType::LinearClicking,
-                ScenarioType::PreciseTracking,
-                ScenarioType::ReactiveTracking,
-                ScenarioType::ControlTracking,
-                ScenarioType::SpeedSwitching,
-                ScenarioType::EvasiveSwitching,
-                ScenarioType::StabilitySwitching,
-            ],
+            playlist,
+            scenarios: Vec::new(),
+            seed: 0,
+            fixed_seed: None,
         }
     }
 }
 
+impl ScenarioState {
+    // Countdown before the next scheduled scenario, or before the results after the last one
+    fn reset_countdown(&mut self) {
+        let seconds = self.scenarios.get(self.current_index).map_or(self.playlist.countdown, |run| run.countdown);
+        self.delay_timer = Timer::from_seconds(seconds, TimerMode::Once);
+    }
+}
+
+// Sent by manage_scenarios as each scenario in the sequence begins and ends, and once the whole sequence is done
+#[derive(Event, Debug, Clone, Copy)]
+enum ScenarioEvent {
+    Started,
+    Ended { index: usize, scenario: ScenarioType },
+    Completed,
+}
+
 #[derive(Debug, Clone, Default, Component, Reflect)]
 #[reflect(Component, Default)]
-pub struct Target;
+pub struct Target {
+    pub radius: f32,
+}
+
+// When a target appeared and when the crosshair first reached it, added to every new target by track_target_spawns
+#[derive(Component, Debug, Default)]
+struct TargetTimeline {
+    spawned: f32,
+    spawn_forward: Vec3, // Camera direction when the target appeared
+    visible: Option<f32>, // First frame inside the camera's view
+    started: Option<f32>, // When it became the player's next target, stamped with first_close
+    first_close: Option<f32>,
+    first_on_target: Option<f32>,
+}
 
 #[derive(Component, Debug)]
 struct TargetMovement {
     velocity: Vec3,
-    pattern: MovementPattern,
+    params: Movement,
     timer: f32,
     start_position: Vec3,
-    max_speed: f32,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum MovementPattern {
-    Static,   // No movement
-    Linear,   // Simple linear movement with bouncing
-    Circular, // Circular or figure-8 patterns
-    Random,   // Random movement with direction changes
+    position: Vec3, // At the latest fixed step; the transform is interpolated towards it
+    previous: Vec3, // At the fixed step before
+    rng: ChaCha8Rng, // Direction changes, seeded at spawn so they don't depend on how many targets move
 }
 
 #[derive(Component)]
 struct ScenarioDisplay;
 
 #[derive(Component)]
-struct PointsDisplay;
+struct CrosshairDot;
+
+#[derive(Component)]
+struct ScoreDisplay;
 
 #[derive(Component)]
 struct FpsDisplay;
 
-#[derive(Default, Resource)]
-struct Points { pub value: i32 }
+#[derive(Component)]
+struct SensitivityDisplay;
 
 #[derive(Component)]
-struct ShootTracker { stopwatch: Stopwatch }
+struct ShootTracker {
+    stopwatch: Stopwatch,
+    last_forward: Option<Vec3>, // Camera direction at the previous shot
+}
 
 fn main() {
+    let mut profile = Profile::from_startup(arg_value("--profile"));
+
+    // Import a shared profile bundle, or export this profile as one and exit
+    if bundle::run_cli(&mut profile, arg_value) {
+        return;
+    }
+
+    // Load a Raw Accel config as this session's curve. Like the export it needs the game's own sensitivity, which
+    // its sens multiplier scales to the cm/360.
+    if let Some(path) = arg_value("--import-rawaccel") {
+        match (rawaccel::import(path.as_ref()), game_cm_per_360(&profile)) {
+            (Err(err), _) => eprintln!("Failed to import {}: {}", path, err),
+            (Ok(_), None) => eprintln!("Raw Accel import needs the game's own sensitivity: --game-sens <{} sensitivity> or --game-cm360 <cm/360>",
+                                       profile.game().name),
+            (Ok(imported), Some(game_cm_per_360)) => {
+                let name = imported.curve.name();
+                match imported.apply_to(&mut profile, game_cm_per_360) {
+                    Ok(()) => println!("Imported {} curve from {}", name, path),
+                    Err(err) => eprintln!("Failed to import {}: {}", path, err),
+                }
+            },
+        }
+    }
+
+    // Load a libinput custom acceleration profile as this session's curve
+    if let Some(path) = arg_value("--import-libinput") {
+        match libinput::import(path.as_ref(), profile.mouse_dpi) {
+            Ok(curve) => {
+                println!("Imported libinput custom profile from {}", path);
+                profile.curve = curve;
+            },
+            Err(err) => eprintln!("Failed to import {}: {}", path, err),
+        }
+    }
+
+    // Replay the targets of a recorded session
+    let fixed_seed = arg_value("--seed").and_then(|value| value.parse::<u64>()
+        .map_err(|_| eprintln!("Invalid --seed '{}', expected a whole number", value)).ok());
+
+    // Run a specific playlist this session
+    if let Some(name) = arg_value("--playlist") {
+        match playlist::find(&name) {
+            Some(playlist) => profile.playlist = playlist.name.clone(),
+            None => eprintln!("Unknown playlist '{}', see --list-playlists", name),
+        }
+    }
+
+    // List the loaded scenario definitions or playlists and exit
+    if scenarios::run_cli() || playlist::run_cli() {
+        return;
+    }
+
+    // Convert between cm/360 and game sensitivities and exit
+    if games::run_cli(profile.sensitivity_cm_per_360, profile.mouse_dpi, arg_value) {
+        return;
+    }
+
+    // Print stored session history and exit
+    if history::run_cli(arg_value) {
+        return;
+    }
+
+    // Export the profile's curve for Raw Accel and exit. Needs the game's own sensitivity.
+    if let Some(path) = arg_value("--export-rawaccel") {
+        let Some(game_cm_per_360) = game_cm_per_360(&profile) else {
+            eprintln!("Raw Accel export needs the game's own sensitivity: --game-sens <{} sensitivity> or --game-cm360 <cm/360>",
+                      profile.game().name);
+            return;
+        };
+        match rawaccel::export(&profile, game_cm_per_360, path.as_ref()) {
+            Ok(()) => println!("Wrote Raw Accel settings for profile '{}' to {}", profile.name, path),
+            Err(err) => eprintln!("Failed to export {}: {}", path, err),
+        }
+        return;
+    }
+
+    // Export the profile's curve as a libinput custom profile with config snippets and exit
+    if let Some(path) = arg_value("--export-libinput") {
+        match libinput::export(&profile, path.as_ref()) {
+            Ok(()) => println!("Wrote libinput profile for profile '{}' to {}", profile.name, path),
+            Err(err) => eprintln!("Failed to export {}: {}", path, err),
+        }
+        return;
+    }
+
     App::new()
         .insert_resource(AmbientLight { color: Color::WHITE, brightness: 2000.0 })
         .insert_resource(ClearColor(Color::srgb(0.1, 0.1, 0.15)))
-        .insert_resource(Points::default())
-        .insert_resource(ScenarioState::default())
+        .insert_resource(Time::<Fixed>::from_hz(FIXED_HZ))
+        .insert_resource(SessionResults::default())
+        .insert_resource(ScenarioState { fixed_seed, ..default() })
+        .insert_resource(SeededRng::default())
+        .insert_resource(profile)
+        .insert_resource(Calibration::default())
+        .insert_resource(FlickTracker::default())
+        .insert_resource(ShotLog::default())
+        .insert_resource(CameraView::default())
+        .insert_resource(MotionRecorder::default())
+        .insert_resource(TrackingRecorder::default())
+        .insert_resource(PendingSmoothness::default())
+        .insert_resource(SessionRecords::from_history(&history::load()))
+        .insert_resource(PendingJournal(journal::load()))
+        .insert_resource(ProfileBeforeResume::default())
+        .add_event::<ScenarioEvent>()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: String::from("Aim Trainer"),
@@ -136,24 +282,73 @@ fn main() {
         }))
         .add_plugins((FrameTimeDiagnosticsPlugin::default(),
                      RapierPhysicsPlugin::<NoUserData>::default(), FpsControllerPlugin))
-        .add_systems(Startup, (setup, fps_controller_setup.in_set(FpsControllerSetup)))
+        .add_systems(Startup, (setup, fps_controller_setup.in_set(FpsControllerSetup), journal::spawn_prompt))
+        .add_systems(FixedUpdate, (update_target_movements, refill_scenario_targets))
+        .add_systems(PreUpdate, apply_mouse_curve.after(fps_controller_input).before(fps_controller_look))
         .add_systems(Update, (
             respawn,
             manage_cursor,
-            click_targets,
+            track_target_spawns,
+            reaction::update_target_timelines.after(track_target_spawns).before(click_targets),
+            miss::update_target_velocities.after(interpolate_target_movements).before(click_targets),
+            click_targets.after(track_target_spawns),
+            scoring::score_tracking,
             update_displays,
             manage_scenarios,
-            update_target_movements,
+            interpolate_target_movements,
+            switch_profile,
+            switch_game,
+            apply_profile,
+            fov::update_camera_view,
+            calibration::run_calibration.after(manage_scenarios),
+            flick::fit_curve_on_key,
+            telemetry::save_scenario_telemetry.after(manage_scenarios),
+            reaction::save_reactions.after(manage_scenarios),
+            shots::save_shot_log.after(manage_scenarios),
+        ))
+        .add_systems(Update, (smoothness::record_tracking, smoothness::save_smoothness.after(manage_scenarios),
+                              smoothness::collect_smoothness.after(smoothness::save_smoothness),
+                              flick::reset_on_scenario_start.after(manage_scenarios),
+                              reset_shoot_tracker.after(manage_scenarios).before(click_targets)))
+        .add_systems(Update, (
+            // Sees the final calibration block's completion while the calibration still counts as running
+            results_screen::show_results_screen.after(smoothness::collect_smoothness).before(calibration::run_calibration),
+            results_screen::handle_results_buttons,
+            results_screen::hide_results_screen,
+            history::save_history.after(smoothness::collect_smoothness).before(calibration::run_calibration),
+            // Journals the cards with the smoothness computed so far; the latest one's arrives with the next journal
+            journal::write_journal.after(smoothness::collect_smoothness).before(calibration::run_calibration),
+            // Sees the start key before it starts a fresh sequence
+            journal::handle_journal_prompt.before(manage_scenarios),
+            // Once the resumed sequence has been recorded with the settings it was played at
+            journal::restore_profile.after(history::save_history).after(results_screen::show_results_screen),
+            switch_playlist,
         ))
         .run();
 }
 
 
 
+// Value following a `--flag` on the command line
+fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != flag);
+    args.next().and(args.next())
+}
+
+// The game's own sensitivity Raw Accel scales, as `--game-cm360 <cm/360>` or `--game-sens <sensitivity>` in the
+// profile's game at its DPI
+fn game_cm_per_360(profile: &Profile) -> Option<f32> {
+    match (arg_value("--game-cm360"), arg_value("--game-sens")) {
+        (Some(cm), _) => cm.parse::<f32>().ok(),
+        (None, Some(sensitivity)) => sensitivity.parse::<f32>().ok().map(|sens| profile.game().cm_per_360(sens, profile.mouse_dpi)),
+        (None, None) => None,
+    }
+}
+
 // Setup player and camera
-fn fps_controller_setup(mut commands: Commands) {
-    // Calculate sensitivity based on cm/360
-    let sensitivity = TAU / (SENSITIVITY_CM_PER_360 / 2.54 * MOUSE_DPI);
+fn fps_controller_setup(mut commands: Commands, view: Res<CameraView>) {
+    // Mouse look is driven by apply_mouse_curve, so the controller's own linear sensitivity is disabled
+    let sensitivity = 0.0;
 
     // Create player entity
     let player = commands.spawn_empty()
@@ -173,7 +368,7 @@ fn fps_controller_setup(mut commands: Commands) {
         .insert(FpsControllerInput { pitch: 0.0, yaw: 0.0, ..default() })
         .insert(FpsController { air_acceleration: 80.0, sensitivity, ..default() })
         .insert(CameraConfig { height_offset: CAMERA_HEIGHT_OFFSET })
-        .insert(ShootTracker { stopwatch: Stopwatch::new() })
+        .insert(ShootTracker { stopwatch: Stopwatch::new(), last_forward: None })
         .insert(SpatialListener::new(0.5))
         .id();
 
@@ -181,7 +376,7 @@ fn fps_controller_setup(mut commands: Commands) {
     commands.spawn((
         Camera3d::default(),
         Camera { order: 0, ..default() },
-        Projection::Perspective(PerspectiveProjection { fov: CAMERA_FOV, ..default() }),
+        Projection::Perspective(PerspectiveProjection { fov: view.vertical_fov, ..default() }),
         Exposure::SUNLIGHT,
         RenderPlayer { logical_entity: player },
     ));
@@ -192,6 +387,8 @@ fn setup(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut materials2d: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<SeededRng>,
+    profile: Res<Profile>,
 ) {
     // Setup 2D camera for UI
     commands.spawn((Camera2d, Camera { order: 2, ..default() }));
@@ -280,30 +477,173 @@ fn setup(
 
     // Spawn initial targets
     for _ in 0..10 {
-        spawn_random_target(&mut commands, &mut meshes, &mut materials);
+        spawn_random_target(&mut commands, &mut meshes, &mut materials, &mut rng);
     }
 
-    // UI elements - dot crosshair
-    let crosshair_material = materials2d.add(Color::srgb(0.0, 1.0, 1.0));
-    let dot_size = 2.0; // Size of the dot in pixels
-    commands.spawn((Mesh2d(meshes.add(Cuboid::new(dot_size, dot_size, 0.0))),
+    // UI elements - dot crosshair, a unit square scaled to the profile's size in pixels
+    let crosshair_material = materials2d.add(crosshair_color(&profile.crosshair));
+    commands.spawn((Mesh2d(meshes.add(Cuboid::new(1.0, 1.0, 0.0))),
                    MeshMaterial2d(crosshair_material),
-                   Transform::default()));
+                   Transform::from_scale(Vec3::splat(profile.crosshair.size)),
+                   CrosshairDot));
 
     // Text displays
-    commands.spawn((Text::new("Points: 0"),
+    commands.spawn((Text::new("Score: 0"),
                    Node { position_type: PositionType::Absolute, bottom: Val::Px(5.), left: Val::Px(15.), ..default() },
-                   PointsDisplay));
+                   ScoreDisplay));
     commands.spawn((Text::new("FPS: 0"),
                    Node { position_type: PositionType::Absolute, top: Val::Px(5.), right: Val::Px(15.), ..default() },
                    FpsDisplay));
-    commands.spawn((Text::new(format!("Sensitivity: {:.1} cm/360 @ {} DPI", SENSITIVITY_CM_PER_360, MOUSE_DPI as i32)),
-                   Node { position_type: PositionType::Absolute, bottom: Val::Px(5.), right: Val::Px(15.), ..default() }));
-    commands.spawn((Text::new("Press SPACE to start scenarios"),
+    commands.spawn((Text::new(sensitivity_text(&profile)),
+                   Node { position_type: PositionType::Absolute, bottom: Val::Px(5.), right: Val::Px(15.), ..default() },
+                   SensitivityDisplay));
+    commands.spawn((Text::new(idle_text(&profile)),
                    Node { position_type: PositionType::Absolute, top: Val::Px(50.), left: Val::Px(15.), ..default() },
                    ScenarioDisplay));
 }
 
+fn crosshair_color(crosshair: &Crosshair) -> Color {
+    let [r, g, b] = crosshair.color;
+    Color::srgb(r, g, b)
+}
+
+fn idle_text(profile: &Profile) -> String {
+    let keybinds = &profile.keybinds;
+    let playlist = playlist::find_or_default(&profile.playlist);
+    format!("Playlist: {} ({} scenarios, {:.0}s), {} to change\nPress {} to start scenarios, {} to calibrate",
+            playlist.name, playlist.runs(), playlist.total_seconds(), profile::key_name(keybinds.switch_playlist),
+            profile::key_name(keybinds.start), profile::key_name(keybinds.calibrate))
+}
+
+fn sensitivity_text(profile: &Profile) -> String {
+    let game = profile.game();
+    format!("Profile: {} | Sensitivity: {:.1} cm/360 @ {} DPI | {}: {:.3} | FOV: {:.0} {:?} | Curve: {}",
+            profile.name, profile.sensitivity_cm_per_360, profile.mouse_dpi as i32,
+            game.name, game.sensitivity(profile.sensitivity_cm_per_360, profile.mouse_dpi),
+            profile.camera_fov, profile.fov_axis, profile.curve.name())
+}
+
+// Cycle to the next profile in the config dir with F2 (only between scenario runs)
+fn switch_profile(key: Res<ButtonInput<KeyCode>>, scenario_state: Res<ScenarioState>,
+                  calibration: Res<Calibration>, mut profile: ResMut<Profile>) {
+    if !key.just_pressed(profile.keybinds.switch_profile) || scenario_state.has_started || calibration.is_running() {
+        return;
+    }
+
+    let names = Profile::list();
+    let Some(next) = names.iter().position(|name| *name == profile.name)
+        .map_or(names.first(), |i| names.get((i + 1) % names.len())) else { return };
+    if *next == profile.name {
+        return;
+    }
+
+    match Profile::load(next) {
+        Ok(loaded) => {
+            profile::write_active_name(&loaded.name);
+            println!("Switched to profile '{}'", loaded.name);
+            *profile = loaded;
+        },
+        Err(err) => eprintln!("Failed to switch to profile '{}': {}", next, err),
+    }
+}
+
+// Cycle the game shown next to cm/360 with F3 (only between runs) and remember it in the profile
+fn switch_game(key: Res<ButtonInput<KeyCode>>, scenario_state: Res<ScenarioState>,
+               calibration: Res<Calibration>, mut profile: ResMut<Profile>) {
+    if !key.just_pressed(profile.keybinds.switch_game) || scenario_state.has_started || calibration.is_running() {
+        return;
+    }
+
+    let index = games::GAMES.iter().position(|game| game.id == profile.game().id).unwrap_or_default();
+    profile.game = games::GAMES[(index + 1) % games::GAMES.len()].id.to_string();
+    if let Err(err) = profile.save() {
+        eprintln!("Failed to save profile: {}", err);
+    }
+}
+
+// Cycle the playlist the next sequence runs with F4 (only between runs) and remember it in the profile
+fn switch_playlist(key: Res<ButtonInput<KeyCode>>, scenario_state: Res<ScenarioState>,
+                   calibration: Res<Calibration>, mut profile: ResMut<Profile>) {
+    if !key.just_pressed(profile.keybinds.switch_playlist) || scenario_state.has_started || calibration.is_running() {
+        return;
+    }
+
+    let next = playlist::next(&profile.playlist);
+    println!("Playlist: {} - {}", next.name, next.description);
+    profile.playlist = next.name.clone();
+    if let Err(err) = profile.save() {
+        eprintln!("Failed to save profile: {}", err);
+    }
+}
+
+// Push profile changes into the HUD and crosshair
+fn apply_profile(profile: Res<Profile>, mut text_query: Query<&mut Text, With<SensitivityDisplay>>,
+                 mut crosshair_query: Query<(&mut Transform, &MeshMaterial2d<ColorMaterial>), With<CrosshairDot>>,
+                 mut materials2d: ResMut<Assets<ColorMaterial>>) {
+    if !profile.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.0 = sensitivity_text(&profile);
+    }
+    if let Ok((mut transform, material)) = crosshair_query.get_single_mut() {
+        transform.scale = Vec3::splat(profile.crosshair.size);
+        if let Some(material) = materials2d.get_mut(&material.0) {
+            material.color = crosshair_color(&profile.crosshair);
+        }
+    }
+}
+
+// Turn each mouse motion event into yaw/pitch using the profile's base sensitivity (matched to the
+// current FOV) and curve gain.
+// Bevy batches events per frame without timestamps, so events are assumed evenly spaced over the frame.
+fn apply_mouse_curve(
+    time: Res<Time>,
+    profile: Res<Profile>,
+    view: Res<CameraView>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut flick_tracker: ResMut<FlickTracker>,
+    mut recorder: ResMut<MotionRecorder>,
+    mut mouse_events: EventReader<MouseMotion>,
+    mut query: Query<(&FpsController, &mut FpsControllerInput)>,
+) {
+    let events: Vec<Vec2> = mouse_events.read().map(|event| event.delta).collect();
+    let Ok((controller, mut input)) = query.get_single_mut() else { return };
+    if !controller.enable_input || events.is_empty() {
+        return;
+    }
+
+    let ms_per_event = (time.delta_secs() * 1000.0 / events.len() as f32).max(f32::EPSILON);
+    let base = profile.radians_per_count();
+    let frame_start = recorder.now() - time.delta_secs_f64();
+    let game_frame_start = time.elapsed_secs() - time.delta_secs();
+    let firing = buttons.pressed(MouseButton::Left);
+    let aiming_down_sights = profile.ads_fov.is_some() && buttons.pressed(MouseButton::Right);
+    for (i, delta) in events.into_iter().enumerate() {
+        let speed = delta.length() / ms_per_event; // counts/ms
+        let curve_radians_per_count = base * profile.curve.gain(speed);
+        let turn = delta * curve_radians_per_count * view.sensitivity_scale;
+        let (yaw, pitch) = (input.yaw, input.pitch);
+        input.pitch = (input.pitch - turn.y).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+        input.yaw -= turn.x;
+        let event_time = game_frame_start + (i + 1) as f32 * ms_per_event / 1000.0;
+        flick_tracker.record_motion(speed, Vec2::new(turn.x, -turn.y), curve_radians_per_count, view.sensitivity_scale,
+                                    aiming_down_sights, event_time);
+        recorder.record(MotionSample {
+            time: frame_start + (i + 1) as f64 * ms_per_event as f64 / 1000.0,
+            dx: delta.x.round() as i32,
+            dy: delta.y.round() as i32,
+            yaw_delta: yaw - input.yaw,
+            pitch_delta: input.pitch - pitch,
+            yaw: input.yaw,
+            pitch: input.pitch,
+            firing,
+            aiming_down_sights,
+        });
+    }
+}
+
 fn respawn(mut query: Query<(&mut Transform, &mut Velocity)>) {
     for (mut transform, mut velocity) in &mut query {
         if transform.translation.y <= -50.0 {
@@ -318,13 +658,16 @@ fn manage_cursor(
     key: Res<ButtonInput<KeyCode>>,
     mut window_query: Query<&mut Window>,
     mut controller_query: Query<&mut FpsController>,
+    results_screen: Query<(), With<ResultsScreen>>,
+    profile: Res<Profile>,
 ) {
     let Ok(mut window) = window_query.get_single_mut() else { return };
     let Ok(mut controller) = controller_query.get_single_mut() else { return };
 
-    if btn.just_pressed(MouseButton::Left) {
+    // Clicks on the results screen go to its buttons
+    if btn.just_pressed(MouseButton::Left) && results_screen.is_empty() {
         set_cursor_state(&mut window, &mut controller, true);
-    } else if key.just_pressed(KeyCode::Escape) {
+    } else if key.just_pressed(profile.keybinds.release_cursor) {
         set_cursor_state(&mut window, &mut controller, false);
     }
 }
@@ -343,9 +686,14 @@ fn click_targets(
     player_query: Query<Entity, With<LogicalPlayer>>,
     camera: Query<&Transform, With<RenderPlayer>>,
     buttons: Res<ButtonInput<MouseButton>>,
-    targets: Query<Entity, With<Target>>,
-    mut points: ResMut<Points>,
+    targets: Query<(&Target, Option<&TargetTimeline>)>,
+    target_transforms: Query<(Entity, &Transform, Option<&TargetVelocity>), With<Target>>,
+    mut results: ResMut<SessionResults>,
     mut shoot_stopwatch: Query<&mut ShootTracker>,
+    mut flick_tracker: ResMut<FlickTracker>,
+    mut shot_log: ResMut<ShotLog>,
+    mut rng: ResMut<SeededRng>,
+    recorder: Res<MotionRecorder>,
     time: Res<Time>,
 ) {
     // Get player and check if we can shoot
@@ -353,24 +701,83 @@ fn click_targets(
     let mut shoot_tracker = shoot_stopwatch.get_mut(player_handle).unwrap();
     shoot_tracker.stopwatch.tick(time.delta());
 
-    // Early returns if not shooting or on cooldown
-    if !buttons.pressed(MouseButton::Left) || shoot_tracker.stopwatch.elapsed_secs() <= 0.1 {
+    // Early returns if not shooting, on cooldown or tracking (scored every frame by score_tracking)
+    let tracking = results.current.scenario.is_some_and(ScenarioType::is_tracking);
+    if !buttons.pressed(MouseButton::Left) || shoot_tracker.stopwatch.elapsed_secs() <= 0.1 || tracking {
         return;
     }
 
-    // Cast ray from camera
+    // Process hit and reset cooldown
     let camera_transform = camera.single();
-    let ray_pos = camera_transform.translation;
-    let ray_dir = camera_transform.forward().as_vec3();
-    let max_distance = (ARENA_WIDTH.powi(2) + ARENA_DEPTH.powi(2) + ARENA_HEIGHT.powi(2)).sqrt() * 1.5;
+    let hit_result = cast_crosshair_ray(&rapier_context, camera_transform, player_handle);
+    let hit = hit_result.is_some_and(|(entity, _)| targets.get(entity).is_ok());
+    let timeline = hit_result.and_then(|(entity, _)| targets.get(entity).ok()).and_then(|(_, timeline)| timeline);
+    let spawned = timeline.map(|timeline| timeline.spawned);
+
+    // Kills in clicking scenarios are pointing movements from the previous shot, or from where the crosshair was
+    // when the target spawned if that was later
+    let clicking = results.current.scenario.is_some_and(ScenarioType::is_clicking);
+    let killed = hit_result.filter(|_| hit).and_then(|(entity, _)| target_transforms.get(entity).ok()).map(|(_, killed, _)| killed);
+    let radius = hit_result.and_then(|(entity, _)| targets.get(entity).ok()).map_or(TARGET_SIZE, |(target, _)| target.radius);
+    if let (true, Some(killed)) = (clicking, killed) {
+        let since_shot = shoot_tracker.stopwatch.elapsed_secs();
+        let since_spawn = spawned.map_or(f32::INFINITY, |spawned| time.elapsed_secs() - spawned);
+        let start = match (shoot_tracker.last_forward, timeline) {
+            (Some(previous), _) if since_shot <= since_spawn => Some((previous, since_shot)),
+            (_, Some(timeline)) => Some((timeline.spawn_forward, since_spawn)),
+            (previous, None) => previous.map(|previous| (previous, since_shot)),
+        };
+        if let Some((start, movement_time)) = start {
+            results.current.fitts.push(fitts::sample(camera_transform, start, killed.translation, radius, movement_time));
+        }
+    }
 
-    // Process hit and reset cooldown
-    let filter = QueryFilter::new().exclude_sensors().exclude_rigid_body(player_handle);
-    process_hit_result(
-        rapier_context.single().cast_ray(ray_pos, ray_dir, max_distance, true, filter),
-        &mut commands, &mut meshes, &mut materials, &targets, &mut points
-    );
+    process_hit_result(hit_result, &mut commands, &mut meshes, &mut materials, &mut rng, &targets, &mut results,
+                       time.elapsed_secs());
     shoot_tracker.stopwatch.reset();
+    shoot_tracker.last_forward = Some(camera_transform.forward().as_vec3());
+
+    // Record the flick that led to this shot, measured against the target closest to the crosshair
+    let nearest = target_transforms.iter()
+        .map(|(entity, target, velocity)| (entity, target, velocity, flick::angular_offset(camera_transform, target.translation)))
+        .min_by(|a, b| a.3.length().total_cmp(&b.3.length()));
+    let error = nearest.map(|(_, _, _, error)| error);
+
+    // Resolve misses against that target
+    if let (false, Some((_, target, velocity, _))) = (hit, nearest) {
+        let velocity = velocity.map_or(Vec3::ZERO, |velocity| velocity.velocity);
+        results.current.missed_shots.push(MissSample::new(camera_transform, target.translation, velocity));
+    }
+
+    // Log the shot against the target it hit, or the nearest one for a miss
+    let hit_target = hit_result.filter(|_| hit).and_then(|(entity, _)| target_transforms.get(entity).ok());
+    let shot_target = hit_target.or(nearest.map(|(entity, target, velocity, _)| (entity, target, velocity)));
+    let (yaw, pitch, _) = camera_transform.rotation.to_euler(EulerRot::YXZ);
+    shot_log.shots.push(ShotEvent {
+        time: recorder.now(),
+        scenario: results.current.scenario,
+        index: results.current.shots_fired().saturating_sub(1),
+        hit,
+        target: shot_target.map(|(entity, _, _)| entity),
+        target_position: shot_target.map_or(Vec3::NAN, |(_, target, _)| target.translation),
+        target_velocity: shot_target.and_then(|(_, _, velocity)| velocity).map_or(Vec3::NAN, |velocity| velocity.velocity),
+        yaw,
+        pitch,
+        error: shot_target.map_or(Vec2::NAN, |(_, target, _)| flick::angular_offset(camera_transform, target.translation)),
+        since_spawn: shot_target.and_then(|(entity, _, _)| targets.get(entity).ok()).and_then(|(_, timeline)| timeline)
+            .map(|timeline| time.elapsed_secs() - timeline.spawned),
+    });
+    if let Some(sample) = flick_tracker.finish_shot(error, hit, time.elapsed_secs(), spawned) {
+        results.current.flicks.extend(sample.segmentation);
+        shot_log.flicks.push(sample);
+    }
+}
+
+// First collider under the crosshair, ignoring the player's own body
+fn cast_crosshair_ray(rapier_context: &ReadRapierContext, camera_transform: &Transform, player: Entity) -> Option<(Entity, f32)> {
+    let max_distance = (ARENA_WIDTH.powi(2) + ARENA_DEPTH.powi(2) + ARENA_HEIGHT.powi(2)).sqrt() * 1.5;
+    let filter = QueryFilter::new().exclude_sensors().exclude_rigid_body(player);
+    rapier_context.single().cast_ray(camera_transform.translation, camera_transform.forward().as_vec3(), max_distance, true, filter)
 }
 
 fn process_hit_result(
@@ -378,33 +785,57 @@ fn process_hit_result(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
-    targets: &Query<Entity, With<Target>>,
-    points: &mut ResMut<Points>,
+    rng: &mut SeededRng,
+    targets: &Query<(&Target, Option<&TargetTimeline>)>,
+    results: &mut ResMut<SessionResults>,
+    now: f32,
 ) {
-    // Adjust points based on hit result
+    // Score the shot on the live card
     match hit_result {
         Some((entity, _)) if targets.get(entity).is_ok() => {
-            // Hit a target - add point, despawn it, spawn a new one
+            // Hit a target - record the kill, despawn it, spawn a new one
+            let reaction = targets.get(entity).ok().and_then(|(_, timeline)| timeline).map(|timeline| timeline.finish(Some(now)));
             commands.entity(entity).despawn_recursive();
-            spawn_random_target(commands, meshes, materials);
-            points.value += 1;
+            respawn_target(commands, meshes, materials, rng, results.current.scenario);
+            results.current.record_hit(reaction);
         },
-        _ => points.value -= 1, // Missed or hit non-target - subtract point
+        _ => results.current.record_miss(), // Missed or hit non-target
+    }
+}
+
+// Stamp newly spawned targets with their spawn time and the crosshair direction, and start tracking their velocity
+fn track_target_spawns(mut commands: Commands, time: Res<Time>, new_targets: Query<Entity, Added<Target>>,
+                       camera: Query<&Transform, With<RenderPlayer>>) {
+    let spawn_forward = camera.get_single().map_or(Vec3::NEG_Z, |camera| camera.forward().as_vec3());
+    for entity in &new_targets {
+        let timeline = TargetTimeline { spawned: time.elapsed_secs(), spawn_forward, ..default() };
+        commands.entity(entity).insert((timeline, TargetVelocity::default()));
+    }
+}
+
+// A scenario's first kill is measured from where its targets appeared, not from a shot before it started
+fn reset_shoot_tracker(mut events: EventReader<ScenarioEvent>, mut trackers: Query<&mut ShootTracker>) {
+    if events.read().any(|event| matches!(event, ScenarioEvent::Started)) {
+        for mut tracker in &mut trackers {
+            tracker.last_forward = None;
+        }
     }
 }
 
 // Update all UI displays
 fn update_displays(
-    points: Res<Points>,
+    results: Res<SessionResults>,
     diagnostics: Res<DiagnosticsStore>,
-    mut points_query: Query<&mut Text, With<PointsDisplay>>,
-    mut fps_query: Query<&mut Text, (With<FpsDisplay>, Without<PointsDisplay>)>,
-    mut scenario_query: Query<&mut Text, (With<ScenarioDisplay>, Without<PointsDisplay>, Without<FpsDisplay>)>,
+    mut score_query: Query<&mut Text, With<ScoreDisplay>>,
+    mut fps_query: Query<&mut Text, (With<FpsDisplay>, Without<ScoreDisplay>)>,
+    mut scenario_query: Query<&mut Text, (With<ScenarioDisplay>, Without<ScoreDisplay>, Without<FpsDisplay>)>,
     scenario_state: Res<ScenarioState>,
+    calibration: Res<Calibration>,
+    profile: Res<Profile>,
 ) {
-    // Update points display
-    if let Ok(mut text) = points_query.get_single_mut() {
-        text.0 = format!("Points: {}", points.value);
+    // Update live score card
+    if let Ok(mut text) = score_query.get_single_mut() {
+        text.0 = results.current.summary();
     }
 
     // Update FPS display
@@ -418,39 +849,36 @@ fn update_displays(
     // Update scenario display
     if let Ok(mut text) = scenario_query.get_single_mut() {
         text.0 = if !scenario_state.has_started {
-            "Press SPACE to start scenarios".to_string()
+            idle_text(&profile)
         } else if scenario_state.is_active {
             let scenario_type = scenario_state.current_type.unwrap();
             let remaining = scenario_state.scenario_timer.remaining_secs();
             format!("Current: {:?} - {:.1}s", scenario_type, remaining)
         } else if scenario_state.current_index < scenario_state.scenarios.len() {
-            let next_scenario = scenario_state.scenarios[scenario_state.current_index];
+            let next_scenario = scenario_state.scenarios[scenario_state.current_index].scenario;
             let remaining = scenario_state.delay_timer.remaining_secs();
             format!("Next: {:?} - {:.1}s", next_scenario, remaining)
         } else {
             "All scenarios completed!".to_string()
         };
+
+        if let Some(status) = calibration.status(profile.keybinds.save_calibration) {
+            text.0 = format!("{}\n{}", status, text.0);
+        }
     }
 }
 
 fn manage_scenarios(mut scenario_state: ResMut<ScenarioState>, time: Res<Time>,
                    mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>,
                    mut materials: ResMut<Assets<StandardMaterial>>,
-                   targets: Query<Entity, With<Target>>,
-                   keyboard: Res<ButtonInput<KeyCode>>) {
-    // Start the test sequence when the user presses Space
-    if keyboard.just_pressed(KeyCode::Space) && !scenario_state.has_started {
-        scenario_state.has_started = true;
-        scenario_state.current_index = 0;
-        scenario_state.is_active = false;
-        scenario_state.delay_timer.reset();
-
-        // Clear any existing targets
-        for entity in &targets {
-            commands.entity(entity).despawn_recursive();
-        }
-
-        println!("Starting aim test sequence. First scenario in {} seconds...", SCENARIO_DELAY);
+                   targets: Query<Entity, With<Target>>, timelines: Query<&TargetTimeline, With<Target>>,
+                   keyboard: Res<ButtonInput<KeyCode>>,
+                   mut scenario_events: EventWriter<ScenarioEvent>,
+                   mut results: ResMut<SessionResults>, mut rng: ResMut<SeededRng>, profile: Res<Profile>) {
+    // Start the test sequence when the user presses the start key (Space by default)
+    if keyboard.just_pressed(profile.keybinds.start) && !scenario_state.has_started {
+        let playlist = playlist::find_or_default(&profile.playlist);
+        start_scenario_sequence(&mut scenario_state, &mut results, &mut commands, &targets, playlist, None);
         return;
     }
 
@@ -467,17 +895,22 @@ fn manage_scenarios(mut scenario_state: ResMut<ScenarioState>, time: Res<Time>,
         if scenario_state.delay_timer.just_finished() {
             // Start next scenario if available
             if scenario_state.current_index < scenario_state.scenarios.len() {
-                let scenario_type = scenario_state.scenarios[scenario_state.current_index];
+                let ScheduledScenario { scenario: scenario_type, duration, .. } = scenario_state.scenarios[scenario_state.current_index];
                 scenario_state.current_type = Some(scenario_type);
                 scenario_state.is_active = true;
-                scenario_state.scenario_timer.reset();
+                scenario_state.scenario_timer = Timer::from_seconds(duration, TimerMode::Once);
+                results.start_scenario(scenario_type);
 
-                spawn_scenario_targets(&mut commands, &mut meshes, &mut materials, scenario_type, &targets);
+                // Each scenario draws from its own streams, so it replays the same regardless of the ones before it
+                rng.reseed(scenario_state.seed, scenario_state.current_index);
+                spawn_scenario_targets(&mut commands, &mut meshes, &mut materials, &mut rng, scenario_type, &targets);
+                scenario_events.send(ScenarioEvent::Started);
                 println!("Starting scenario: {:?}", scenario_type);
             } else {
                 // All scenarios completed
                 scenario_state.has_started = false;
                 scenario_state.current_type = None;
+                scenario_events.send(ScenarioEvent::Completed);
                 println!("All scenarios completed!");
             }
         }
@@ -487,9 +920,15 @@ fn manage_scenarios(mut scenario_state: ResMut<ScenarioState>, time: Res<Time>,
 
         if scenario_state.scenario_timer.just_finished() {
             // End current scenario
+            if let Some(scenario_type) = scenario_state.current_type {
+                scenario_events.send(ScenarioEvent::Ended { index: scenario_state.current_index, scenario: scenario_type });
+            }
+            // File the targets still alive before their despawns are applied
+            results.current.reactions.extend(timelines.iter().map(|timeline| timeline.finish(None)));
+            results.finish_scenario(scenario_state.scenario_timer.duration().as_secs_f32());
             scenario_state.is_active = false;
             scenario_state.current_index += 1;
-            scenario_state.delay_timer.reset();
+            scenario_state.reset_countdown();
 
             // Clear targets
             for entity in &targets {
@@ -497,143 +936,135 @@ fn manage_scenarios(mut scenario_state: ResMut<ScenarioState>, time: Res<Time>,
             }
 
             if scenario_state.current_index < scenario_state.scenarios.len() {
-                println!("Scenario completed. Next scenario in {} seconds...", SCENARIO_DELAY);
+                println!("Scenario completed. Next scenario in {} seconds...", scenario_state.delay_timer.duration().as_secs_f32());
             }
-        } else if let Some(scenario_type) = scenario_state.current_type {
-            // Update targets for current scenario
-            update_scenario_targets(&mut commands, &mut meshes, &mut materials,
-                                   scenario_type, time.delta_secs(), &targets);
+        } else {
+            results.current.duration = scenario_state.scenario_timer.elapsed_secs();
         }
     }
 }
 
+// Schedule the playlist's scenarios from the first one and clear the arena. Without a seed the sequence plays
+// `--seed`, or a new random one.
+fn start_scenario_sequence(scenario_state: &mut ScenarioState, results: &mut SessionResults,
+                           commands: &mut Commands, targets: &Query<Entity, With<Target>>, playlist: &'static Playlist,
+                           seed: Option<u64>) {
+    scenario_state.has_started = true;
+    results.completed.clear();
+    scenario_state.seed = seed.or(scenario_state.fixed_seed).unwrap_or_else(rng::new_seed);
+    scenario_state.playlist = playlist;
+    scenario_state.scenarios = playlist.schedule(&mut rng::stream(scenario_state.seed, Stream::Playlist, 0));
+    scenario_state.current_index = 0;
+    scenario_state.is_active = false;
+    scenario_state.reset_countdown();
+
+    // Clear any existing targets
+    for entity in targets {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    println!("Starting playlist {} (seed {}). First scenario in {} seconds...", playlist.name, scenario_state.seed,
+             scenario_state.delay_timer.duration().as_secs_f32());
+}
+
+// Pick a journaled sequence back up at its next scenario, with the scenarios it had already completed
+fn resume_scenario_sequence(scenario_state: &mut ScenarioState, results: &mut SessionResults,
+                            commands: &mut Commands, targets: &Query<Entity, With<Target>>,
+                            playlist: &'static Playlist, seed: Option<u64>, scenarios: Vec<ScheduledScenario>,
+                            next_index: usize, completed: Vec<ScoreCard>) {
+    start_scenario_sequence(scenario_state, results, commands, targets, playlist, seed);
+    scenario_state.scenarios = scenarios;
+    scenario_state.current_index = next_index;
+    scenario_state.reset_countdown();
+    results.completed = completed;
+}
+
 // Spawn a target at a random position within the player's field of view
 fn spawn_target_in_fov(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
-                     materials: &mut ResMut<Assets<StandardMaterial>>,
+                     materials: &mut ResMut<Assets<StandardMaterial>>, rng: &mut SeededRng,
                      pattern: Option<MovementPattern>, max_speed: Option<f32>) -> Vec3 {
-    let mut rng = rand::rng();
 
     // If pattern not specified, choose a random one
     let pattern = pattern.unwrap_or_else(|| {
         let patterns = [MovementPattern::Static, MovementPattern::Linear,
                        MovementPattern::Circular, MovementPattern::Random];
-        patterns[rng.random_range(0..patterns.len())]
+        patterns[rng.spawn.random_range(0..patterns.len())]
     });
 
     // If max_speed not specified, choose a random one based on pattern
     let max_speed = max_speed.unwrap_or_else(|| {
-        if pattern == MovementPattern::Static { 0.0 } else { rng.random_range(3.0..10.0) }
+        if pattern == MovementPattern::Static { 0.0 } else { rng.spawn.random_range(3.0..10.0) }
     });
 
     // Generate random position within FOV
     let z = -ARENA_DEPTH/2.0 + 5.0; // Near the front wall
     let fov_width = 2.0 * z.abs(); // Width of FOV at this distance
     let pos = Vec3::new(
-        rng.sample(Uniform::new(-fov_width/2.0, fov_width/2.0).unwrap()),
-        rng.sample(Uniform::new(5.0, ARENA_HEIGHT - 5.0).unwrap()),
+        rng.spawn.sample(Uniform::new(-fov_width/2.0, fov_width/2.0).unwrap()),
+        rng.spawn.sample(Uniform::new(5.0, ARENA_HEIGHT - 5.0).unwrap()),
         z
     );
 
-    spawn_target_with_movement(commands, meshes, materials, pos, pattern, max_speed)
+    let movement = Movement { pattern, speed: max_speed, ..default() };
+    spawn_target_with_movement(commands, meshes, materials, rng, pos, movement, TARGET_SIZE)
 }
 
 // Shorthand for spawning a random target
 fn spawn_random_target(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
-                     materials: &mut ResMut<Assets<StandardMaterial>>) {
-    spawn_target_in_fov(commands, meshes, materials, None, None);
+                     materials: &mut ResMut<Assets<StandardMaterial>>, rng: &mut SeededRng) {
+    spawn_target_in_fov(commands, meshes, materials, rng, None, None);
+}
+
+// Spawn target `index` of a scenario, or a random one of its spawn points for None
+fn spawn_scenario_target(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
+                         materials: &mut ResMut<Assets<StandardMaterial>>, rng: &mut SeededRng,
+                         scenario_type: ScenarioType, index: Option<usize>) {
+    let def = scenario_type.def();
+    let position = def.spawn.position(index, &mut rng.spawn);
+    spawn_target_with_movement(commands, meshes, materials, rng, position, def.movement, def.target_size);
 }
 
 fn spawn_scenario_targets(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
-                         materials: &mut ResMut<Assets<StandardMaterial>>, scenario_type: ScenarioType,
-                         targets: &Query<Entity, With<Target>>) {
+                         materials: &mut ResMut<Assets<StandardMaterial>>, rng: &mut SeededRng,
+                         scenario_type: ScenarioType, targets: &Query<Entity, With<Target>>) {
     // Clear any existing targets first
     for entity in targets.iter() {
         commands.entity(entity).despawn_recursive();
     }
 
-    // Common values
-    let z_wall = -ARENA_DEPTH/2.0 + 5.0; // 5 units from the wall
-
-    // Spawn targets based on scenario type
-    match scenario_type {
-        ScenarioType::StaticClicking => {
-            // Spawn 5 static targets in a circle
-            for i in 0..5 {
-                let angle = (i as f32) * (TAU / 5.0);
-                spawn_target_with_movement(
-                    commands, meshes, materials,
-                    Vec3::new(z_wall.abs() * 0.8 * angle.cos(), 10.0 + (i as f32 * 3.0) % 15.0, z_wall),
-                    MovementPattern::Static, 0.0
-                );
-            }
-        },
-        ScenarioType::DynamicClicking => {
-            // Spawn 3 random moving targets
-            for _ in 0..3 {
-                spawn_target_in_fov(commands, meshes, materials, Some(MovementPattern::Random), Some(10.0));
-            }
-        },
-        ScenarioType::LinearClicking => {
-            // Spawn 3 targets in a row that move linearly
-            for i in 0..3 {
-                spawn_target_with_movement(
-                    commands, meshes, materials,
-                    Vec3::new((i as f32 - 1.0) * 15.0, 10.0 + (i as f32 * 5.0), z_wall),
-                    MovementPattern::Linear, 5.0
-                );
-            }
-        },
-        // Single target tracking scenarios
-        ScenarioType::PreciseTracking => {
-            spawn_target_with_movement(commands, meshes, materials,
-                                      Vec3::new(0.0, 15.0, z_wall), MovementPattern::Circular, 3.0);
-        },
-        ScenarioType::ReactiveTracking => {
-            spawn_target_with_movement(commands, meshes, materials,
-                                      Vec3::new(0.0, 15.0, z_wall), MovementPattern::Random, 15.0);
-        },
-        ScenarioType::ControlTracking => {
-            spawn_target_with_movement(commands, meshes, materials,
-                                      Vec3::new(0.0, 15.0, z_wall), MovementPattern::Circular, 8.0);
-        },
-        // Switching scenarios with multiple targets
-        ScenarioType::SpeedSwitching | ScenarioType::StabilitySwitching => {
-            spawn_multiple_targets(commands, meshes, materials, z_wall, MovementPattern::Static, 0.0);
-        },
-        ScenarioType::EvasiveSwitching => {
-            spawn_multiple_targets(commands, meshes, materials, z_wall, MovementPattern::Random, 12.0);
-        },
+    for i in 0..scenario_type.def().count {
+        spawn_scenario_target(commands, meshes, materials, rng, scenario_type, Some(i));
     }
 }
 
-// Helper function to spawn multiple targets in a row
-fn spawn_multiple_targets(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
-                        materials: &mut ResMut<Assets<StandardMaterial>>, z: f32,
-                        pattern: MovementPattern, speed: f32) {
-    for i in 0..3 {
-        spawn_target_with_movement(
-            commands, meshes, materials,
-            Vec3::new((i as f32 - 1.0) * 20.0, 10.0 + (i as f32 * 5.0), z),
-            pattern, speed
-        );
+// Replace a killed target according to the scenario's respawn policy (free play always spawns a random one)
+fn respawn_target(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
+                  materials: &mut ResMut<Assets<StandardMaterial>>, rng: &mut SeededRng, scenario_type: Option<ScenarioType>) {
+    match scenario_type.map_or(Respawn::Random, |scenario| scenario.def().respawn) {
+        Respawn::Random => spawn_random_target(commands, meshes, materials, rng),
+        Respawn::Replace => spawn_scenario_target(commands, meshes, materials, rng, scenario_type.unwrap(), None),
+        Respawn::None => {},
     }
 }
 
-fn update_scenario_targets(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
-                          materials: &mut ResMut<Assets<StandardMaterial>>,
-                          scenario_type: ScenarioType, _delta_time: f32,
-                          targets: &Query<Entity, With<Target>>) {
-    // Only spawn new targets for DynamicClicking if we have fewer than 3
-    if scenario_type == ScenarioType::DynamicClicking {
+// Top refilling scenarios back up to their target count now and then. Rolled per fixed tick rather than per
+// frame, so the same seed draws the same rolls at any frame rate.
+fn refill_scenario_targets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>,
+                           mut materials: ResMut<Assets<StandardMaterial>>, mut rng: ResMut<SeededRng>,
+                           scenario_state: Res<ScenarioState>, targets: Query<Entity, With<Target>>) {
+    let Some(scenario_type) = scenario_state.current_type.filter(|_| scenario_state.is_active) else { return };
+    let def = scenario_type.def();
+    if def.refill_chance > 0.0 {
         let target_count = targets.iter().count();
-        if target_count < 3 && rand::random::<f32>() < 0.1 {
-            spawn_target_in_fov(commands, meshes, materials, Some(MovementPattern::Random), Some(10.0));
+        if target_count < def.count && rng.refill.random::<f32>() < def.refill_chance {
+            spawn_scenario_target(&mut commands, &mut meshes, &mut materials, &mut rng, scenario_type, None);
         }
     }
 }
 
-fn update_target_movements(time: Res<Time>, mut query: Query<(&mut Transform, &mut TargetMovement)>,
-                          camera_query: Query<&Transform, (With<RenderPlayer>, Without<TargetMovement>)>) {
+// Step target paths on the fixed timestep, so the same seed gives the same paths at any frame rate
+fn update_target_movements(time: Res<Time>, mut query: Query<&mut TargetMovement>,
+                          camera_query: Query<&Transform, With<RenderPlayer>>) {
     let delta = time.delta_secs();
     let camera_transform = camera_query.get_single().ok();
 
@@ -642,16 +1073,17 @@ fn update_target_movements(time: Res<Time>, mut query: Query<(&mut Transform, &m
     let z_min = -ARENA_DEPTH/2.0 + margin;
     let z_max = z_min + 30.0;
 
-    for (mut transform, mut movement) in &mut query {
+    for mut movement in &mut query {
         movement.timer += delta;
+        movement.previous = movement.position;
 
         // Calculate FOV-based boundaries
-        let fov_width = 2.0 * transform.translation.z.abs();
+        let fov_width = 2.0 * movement.position.z.abs();
         let bounds_min = Vec3::new(-fov_width/2.0, 5.0, z_min);
         let bounds_max = Vec3::new(fov_width/2.0, ARENA_HEIGHT - 5.0, z_max);
 
         // Update position based on pattern
-        match movement.pattern {
+        match movement.params.pattern {
             MovementPattern::Static => {}, // No movement
 
             MovementPattern::Linear => {
@@ -660,41 +1092,38 @@ fn update_target_movements(time: Res<Time>, mut query: Query<(&mut Transform, &m
                     initialize_velocity(&mut movement, 0.0);
                 }
 
-                apply_velocity(&mut transform, &movement, delta);
-                handle_boundary_collision(&mut transform, &mut movement, bounds_min, bounds_max);
+                apply_velocity(&mut movement, delta);
+                handle_boundary_collision(&mut movement, bounds_min, bounds_max);
             },
 
             MovementPattern::Circular => {
                 // Circular pattern
-                let radius = 15.0;
-                let angle = movement.timer * (movement.max_speed / radius);
+                let radius = movement.params.radius;
+                let angle = movement.timer * (movement.params.speed / radius);
 
-                transform.translation = Vec3::new(
+                movement.position = Vec3::new(
                     movement.start_position.x + radius * angle.cos(),
                     movement.start_position.y + radius * 0.3 * angle.sin(),
                     -ARENA_DEPTH/2.0 + 15.0 + radius * 0.2 * (1.0 - angle.cos())
-                );
-
-                transform.translation = transform.translation.clamp(bounds_min, bounds_max);
+                ).clamp(bounds_min, bounds_max);
             },
 
             MovementPattern::Random => {
                 // Change direction occasionally
-                if movement.velocity.length_squared() < 0.001 || movement.timer > 2.0 {
+                if movement.velocity.length_squared() < 0.001 || movement.timer > movement.params.turn_interval {
                     initialize_velocity(&mut movement, 0.2);
                     movement.timer = 0.0;
                 }
 
-                apply_velocity(&mut transform, &movement, delta);
-                handle_boundary_collision(&mut transform, &mut movement, bounds_min, bounds_max);
+                apply_velocity(&mut movement, delta);
+                handle_boundary_collision(&mut movement, bounds_min, bounds_max);
 
                 // Add evasion for high-speed targets
                 if let Some(camera) = camera_transform {
-                    if movement.max_speed > 10.0 {
-                        let to_camera = (camera.translation - transform.translation).normalize();
+                    if movement.params.evasive {
+                        let to_camera = (camera.translation - movement.position).normalize();
                         let perpendicular = Vec3::new(to_camera.z, 0.0, -to_camera.x).normalize() * delta * 5.0;
-                        transform.translation += perpendicular;
-                        transform.translation = transform.translation.clamp(bounds_min, bounds_max);
+                        movement.position = (movement.position + perpendicular).clamp(bounds_min, bounds_max);
                     }
                 }
             },
@@ -702,9 +1131,17 @@ fn update_target_movements(time: Res<Time>, mut query: Query<(&mut Transform, &m
     }
 }
 
+// Draw moving targets between their last two fixed steps, so they move smoothly at any frame rate
+fn interpolate_target_movements(fixed_time: Res<Time<Fixed>>, mut query: Query<(&mut Transform, &TargetMovement)>) {
+    let fraction = fixed_time.overstep_fraction();
+    for (mut transform, movement) in &mut query {
+        transform.translation = movement.previous.lerp(movement.position, fraction);
+    }
+}
+
 // Helper to initialize velocity
 fn initialize_velocity(movement: &mut TargetMovement, y_range: f32) {
-    let mut rng = rand::rng();
+    let rng = &mut movement.rng;
     let y_component = if y_range > 0.0 {
         rng.sample(Uniform::new(-y_range, y_range).unwrap())
     } else {
@@ -715,40 +1152,39 @@ fn initialize_velocity(movement: &mut TargetMovement, y_range: f32) {
         rng.sample(Uniform::new(-1.0, 1.0).unwrap()),
         y_component,
         rng.sample(Uniform::new(-1.0, 1.0).unwrap())
-    ).normalize() * movement.max_speed;
+    ).normalize() * movement.params.speed;
 }
 
 // Helper to apply velocity
-fn apply_velocity(transform: &mut Transform, movement: &TargetMovement, delta: f32) {
-    transform.translation += movement.velocity * delta;
+fn apply_velocity(movement: &mut TargetMovement, delta: f32) {
+    movement.position += movement.velocity * delta;
 }
 
 // Handle boundary collisions with a single function
-fn handle_boundary_collision(transform: &mut Transform, movement: &mut TargetMovement,
-                           bounds_min: Vec3, bounds_max: Vec3) {
+fn handle_boundary_collision(movement: &mut TargetMovement, bounds_min: Vec3, bounds_max: Vec3) {
     // Check each axis and bounce if needed
     for i in 0..3 {
-        if transform.translation[i] < bounds_min[i] || transform.translation[i] > bounds_max[i] {
+        if movement.position[i] < bounds_min[i] || movement.position[i] > bounds_max[i] {
             movement.velocity[i] = -movement.velocity[i];
         }
     }
 
     // Clamp position to boundaries
-    transform.translation = transform.translation.clamp(bounds_min, bounds_max);
+    movement.position = movement.position.clamp(bounds_min, bounds_max);
 }
 
 fn spawn_target_with_movement(commands: &mut Commands, meshes: &mut ResMut<Assets<Mesh>>,
-                            materials: &mut ResMut<Assets<StandardMaterial>>, position: Vec3,
-                            pattern: MovementPattern, max_speed: f32) -> Vec3 {
+                            materials: &mut ResMut<Assets<StandardMaterial>>, rng: &mut SeededRng,
+                            position: Vec3, movement: Movement, radius: f32) -> Vec3 {
     // Create red glowing target
     let mut entity = commands.spawn((
-        Collider::ball(TARGET_SIZE),
+        Collider::ball(radius),
         RigidBody::Dynamic,
         GravityScale(0.0),
         Sleeping::disabled(),
         Transform::from_translation(position),
-        Target,
-        Mesh3d(meshes.add(Sphere::new(TARGET_SIZE))),
+        Target { radius },
+        Mesh3d(meshes.add(Sphere::new(radius))),
         MeshMaterial3d(materials.add(StandardMaterial {
             base_color: Color::srgb(1.0, 0.1, 0.1),
             emissive: Color::srgb(1.0, 0.2, 0.2).into(),
@@ -758,13 +1194,15 @@ fn spawn_target_with_movement(commands: &mut Commands, meshes: &mut ResMut<Asset
     ));
 
     // Add movement component if not static
-    if pattern != MovementPattern::Static {
+    if movement.pattern != MovementPattern::Static {
         entity.insert(TargetMovement {
             velocity: Vec3::ZERO,
-            pattern,
+            params: movement,
             timer: 0.0,
             start_position: position,
-            max_speed,
+            position,
+            previous: position,
+            rng: rng.target_rng(),
         });
     }
 
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{flick, Target};
+
+const MOVING_THRESHOLD: f32 = 1.0 * std::f32::consts::PI / 180.0; // Targets slower than 1 deg/s count as stationary
+const VELOCITY_LOOKAHEAD: f32 = 0.1; // Seconds ahead used to turn world velocity into angular velocity
+const ELLIPSE_CHI_SQUARED: f32 = 5.991; // 95% confidence for two degrees of freedom
+const MIN_ELLIPSE_MISSES: usize = 3;
+
+// World velocity of a target from its last two frames, whatever its movement pattern
+#[derive(Component, Debug, Default)]
+pub struct TargetVelocity {
+    previous: Option<Vec3>,
+    pub velocity: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MissClass {
+    Lead,  // Ahead of a horizontally moving target
+    Lag,   // Behind it
+    Above,
+    Below,
+    Left,  // Beside a stationary target
+    Right,
+}
+
+// A miss resolved against the target nearest the crosshair
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MissSample {
+    pub offset: Vec2,      // Crosshair relative to the target, radians (right, up)
+    pub approaching: bool, // Target was moving towards the crosshair
+    pub class: MissClass,
+}
+
+impl MissSample {
+    pub fn new(camera: &Transform, target: Vec3, velocity: Vec3) -> Self {
+        let error = flick::angular_offset(camera, target);
+        let ahead = flick::angular_offset(camera, target + velocity * VELOCITY_LOOKAHEAD);
+        let target_motion = (ahead - error) / VELOCITY_LOOKAHEAD;
+        let offset = -error;
+
+        let class = if offset.y.abs() > offset.x.abs() {
+            if offset.y > 0.0 { MissClass::Above } else { MissClass::Below }
+        } else if target_motion.x.abs() > MOVING_THRESHOLD {
+            if offset.x * target_motion.x > 0.0 { MissClass::Lead } else { MissClass::Lag }
+        } else if offset.x > 0.0 {
+            MissClass::Right
+        } else {
+            MissClass::Left
+        };
+
+        Self { offset, approaching: target_motion.dot(offset) > 0.0, class }
+    }
+}
+
+// Mean miss offset and the 95% ellipse around it, in radians
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorEllipse {
+    pub mean: Vec2,
+    pub semi_major: f32,
+    pub semi_minor: f32,
+    pub angle: f32, // Major axis from screen right, counter-clockwise
+}
+
+pub fn error_ellipse(misses: &[MissSample]) -> Option<ErrorEllipse> {
+    if misses.len() < MIN_ELLIPSE_MISSES {
+        return None;
+    }
+    let n = misses.len() as f32;
+    let mean = misses.iter().map(|miss| miss.offset).sum::<Vec2>() / n;
+    let (xx, yy, xy) = misses.iter().fold((0.0, 0.0, 0.0), |(xx, yy, xy), miss| {
+        let d = miss.offset - mean;
+        (xx + d.x * d.x, yy + d.y * d.y, xy + d.x * d.y)
+    });
+    let (xx, yy, xy) = (xx / (n - 1.0), yy / (n - 1.0), xy / (n - 1.0));
+
+    // Eigenvalues of the 2x2 covariance matrix
+    let half_trace = (xx + yy) / 2.0;
+    let spread = (((xx - yy) / 2.0).powi(2) + xy * xy).sqrt();
+    Some(ErrorEllipse {
+        mean,
+        semi_major: (ELLIPSE_CHI_SQUARED * (half_trace + spread)).sqrt(),
+        semi_minor: (ELLIPSE_CHI_SQUARED * (half_trace - spread).max(0.0)).sqrt(),
+        angle: 0.5 * (2.0 * xy).atan2(xx - yy),
+    })
+}
+
+// Miss counts per class plus the error ellipse, for the end-of-scenario report
+pub fn summary(misses: &[MissSample]) -> Option<String> {
+    if misses.is_empty() {
+        return None;
+    }
+    let classes = [MissClass::Lead, MissClass::Lag, MissClass::Above, MissClass::Below, MissClass::Left, MissClass::Right];
+    let counts: Vec<String> = classes.iter()
+        .map(|&class| (class, misses.iter().filter(|miss| miss.class == class).count()))
+        .filter(|&(_, count)| count > 0)
+        .map(|(class, count)| format!("{:?} {}", class, count))
+        .collect();
+    let approaching = misses.iter().filter(|miss| miss.approaching).count();
+    let ellipse = error_ellipse(misses).map_or(String::new(), |ellipse| format!(
+        " | bias ({:+.2}, {:+.2}) deg, 95% ellipse {:.2} x {:.2} deg at {:.0} deg",
+        ellipse.mean.x.to_degrees(), ellipse.mean.y.to_degrees(), ellipse.semi_major.to_degrees(),
+        ellipse.semi_minor.to_degrees(), ellipse.angle.to_degrees()));
+    Some(format!("Misses: {} | {} approaching{}", counts.join(", "), approaching, ellipse))
+}
+
+// Estimate every target's velocity from its movement since the previous frame
+pub fn update_target_velocities(time: Res<Time>, mut targets: Query<(&Transform, &mut TargetVelocity), With<Target>>) {
+    let delta = time.delta_secs();
+    for (transform, mut velocity) in &mut targets {
+        if let Some(previous) = velocity.previous.filter(|_| delta > 0.0) {
+            velocity.velocity = (transform.translation - previous) / delta;
+        }
+        velocity.previous = Some(transform.translation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn misses(offsets: impl IntoIterator<Item = Vec2>) -> Vec<MissSample> {
+        offsets.into_iter().map(|offset| MissSample { offset, approaching: false, class: MissClass::Left }).collect()
+    }
+
+    #[test]
+    fn ellipse_of_known_spread() {
+        // Variance 8/3 along x and 2/3 along y about (0.1, -0.2)
+        let center = Vec2::new(0.1, -0.2);
+        let offsets = [Vec2::new(2.0, 0.0), Vec2::new(-2.0, 0.0), Vec2::new(0.0, 1.0), Vec2::new(0.0, -1.0)];
+        let ellipse = error_ellipse(&misses(offsets.map(|offset| center + offset))).unwrap();
+        assert!(ellipse.mean.distance(center) < 1e-5);
+        assert!((ellipse.semi_major - (ELLIPSE_CHI_SQUARED * 8.0 / 3.0).sqrt()).abs() < 1e-4);
+        assert!((ellipse.semi_minor - (ELLIPSE_CHI_SQUARED * 2.0 / 3.0).sqrt()).abs() < 1e-4);
+        assert!(ellipse.angle.abs() < 1e-5);
+
+        // The same spread turned 45 degrees counter-clockwise
+        let rotation = Vec2::from_angle(std::f32::consts::FRAC_PI_4);
+        let rotated = error_ellipse(&misses(offsets.map(|offset| rotation.rotate(offset)))).unwrap();
+        assert!((rotated.semi_major - ellipse.semi_major).abs() < 1e-4);
+        assert!((rotated.semi_minor - ellipse.semi_minor).abs() < 1e-4);
+        assert!((rotated.angle - std::f32::consts::FRAC_PI_4).abs() < 1e-4, "{}", rotated.angle);
+    }
+
+    #[test]
+    fn ellipse_needs_three_misses() {
+        assert!(error_ellipse(&misses([Vec2::X, Vec2::Y])).is_none());
+        let line = error_ellipse(&misses([Vec2::ZERO, Vec2::X, Vec2::X * 2.0])).unwrap();
+        assert_eq!(line.semi_minor, 0.0);
+    }
+}
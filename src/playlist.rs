@@ -0,0 +1,198 @@
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::{definitions, profile, scenarios::ScenarioType};
+
+pub const DEFAULT_PLAYLIST: &str = "Benchmark";
+
+// Built-in playlists; `<config>/playlists/*.toml` adds more or overrides them (see definitions::load_dir)
+const BUILT_IN: &[(&str, &str)] = &[
+    ("benchmark.toml", include_str!("../playlists/benchmark.toml")),
+    ("warmup.toml", include_str!("../playlists/warmup.toml")),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    pub scenario: ScenarioType,
+    #[serde(default)]
+    pub duration: Option<f32>,  // Seconds, default: the scenario's own duration
+    #[serde(default)]
+    pub countdown: Option<f32>, // Seconds before the scenario starts, default: the playlist's countdown
+    #[serde(default = "one")]
+    pub repeat: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Playlist {
+    #[serde(default)]
+    pub name: String, // Defaults to the file name
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_countdown")]
+    pub countdown: f32, // Also the pause between the last scenario and the results
+    #[serde(default)]
+    pub shuffle: bool,  // Shuffle the expanded runs each time the playlist starts
+    pub entries: Vec<PlaylistEntry>,
+}
+
+fn default_countdown() -> f32 {
+    5.0
+}
+
+// One run of a scenario in a sequence, with the playlist's settings resolved
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledScenario {
+    pub scenario: ScenarioType,
+    pub duration: f32,
+    pub countdown: f32,
+}
+
+impl Playlist {
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_seconds = |value: f32, min: f32| value.is_finite() && (min..=3600.0).contains(&value);
+        if self.entries.is_empty() {
+            return Err("playlist has no entries".into());
+        }
+        if !valid_seconds(self.countdown, 0.0) {
+            return Err("countdown must be between 0 and 3600 seconds".into());
+        }
+        for entry in &self.entries {
+            if entry.duration.is_some_and(|duration| !valid_seconds(duration, 1.0)) {
+                return Err(format!("{:?}: duration must be between 1 and 3600 seconds", entry.scenario));
+            }
+            if entry.countdown.is_some_and(|countdown| !valid_seconds(countdown, 0.0)) {
+                return Err(format!("{:?}: countdown must be between 0 and 3600 seconds", entry.scenario));
+            }
+            if !(1..=100).contains(&entry.repeat) {
+                return Err(format!("{:?}: repeat must be between 1 and 100", entry.scenario));
+            }
+        }
+        Ok(())
+    }
+
+    // The runs this playlist expands to, repeated and (if enabled) shuffled
+    pub fn schedule(&self, rng: &mut impl Rng) -> Vec<ScheduledScenario> {
+        let mut runs: Vec<ScheduledScenario> = self.entries.iter()
+            .flat_map(|entry| {
+                let run = ScheduledScenario {
+                    scenario: entry.scenario,
+                    duration: entry.duration.unwrap_or(entry.scenario.def().duration),
+                    countdown: entry.countdown.unwrap_or(self.countdown),
+                };
+                std::iter::repeat_n(run, entry.repeat as usize)
+            })
+            .collect();
+        if self.shuffle {
+            runs.shuffle(rng);
+        }
+        runs
+    }
+
+    pub fn runs(&self) -> u32 {
+        self.entries.iter().map(|entry| entry.repeat).sum()
+    }
+
+    // Seconds from start to results, countdowns included
+    pub fn total_seconds(&self) -> f32 {
+        self.entries.iter()
+            .map(|entry| entry.repeat as f32 * (entry.duration.unwrap_or(entry.scenario.def().duration)
+                + entry.countdown.unwrap_or(self.countdown)))
+            .sum::<f32>() + self.countdown
+    }
+}
+
+fn load() -> Vec<Playlist> {
+    definitions::load_dir("playlist", BUILT_IN, &profile::config_dir().join("playlists"), |def| &mut def.name, Playlist::validate)
+}
+
+// Every playlist, built-in ones first, loaded on first use
+pub fn registry() -> &'static [Playlist] {
+    static REGISTRY: OnceLock<Vec<Playlist>> = OnceLock::new();
+    REGISTRY.get_or_init(load)
+}
+
+pub fn find(name: &str) -> Option<&'static Playlist> {
+    registry().iter().find(|playlist| playlist.name.eq_ignore_ascii_case(name))
+}
+
+// The named playlist, falling back to the default one
+pub fn find_or_default(name: &str) -> &'static Playlist {
+    find(name).or_else(|| find(DEFAULT_PLAYLIST)).unwrap_or(&registry()[0])
+}
+
+// The playlist after this one, wrapping around
+pub fn next(name: &str) -> &'static Playlist {
+    let playlists = registry();
+    let index = playlists.iter().position(|playlist| playlist.name.eq_ignore_ascii_case(name));
+    &playlists[index.map_or(0, |i| (i + 1) % playlists.len())]
+}
+
+// `--list-playlists` prints the playlists and their runs, then exits
+pub fn run_cli() -> bool {
+    if !std::env::args().any(|arg| arg == "--list-playlists") {
+        return false;
+    }
+    for playlist in registry() {
+        println!("{} - {} ({} runs, ~{:.0}s{})", playlist.name, playlist.description, playlist.runs(),
+                 playlist.total_seconds(), if playlist.shuffle { ", shuffled" } else { "" });
+        for entry in &playlist.entries {
+            println!("  {:?} x{} {:.0}s", entry.scenario, entry.repeat, entry.duration.unwrap_or(entry.scenario.def().duration));
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{self, Stream};
+
+    fn sample(shuffle: bool) -> Playlist {
+        toml::from_str(&format!("countdown = 4.0\nshuffle = {}\n\
+            [[entries]]\nscenario = \"StaticClicking\"\nduration = 20.0\nrepeat = 3\n\
+            [[entries]]\nscenario = \"PreciseTracking\"\ncountdown = 1.0\n", shuffle)).unwrap()
+    }
+
+    #[test]
+    fn schedule_expands_repeats_with_resolved_settings() {
+        let playlist = sample(false);
+        let runs = playlist.schedule(&mut rng::stream(1, Stream::Playlist, 0));
+        let static_clicking = ScheduledScenario { scenario: ScenarioType::find("StaticClicking").unwrap(), duration: 20.0, countdown: 4.0 };
+        let tracking = ScenarioType::find("PreciseTracking").unwrap();
+        let precise_tracking = ScheduledScenario { scenario: tracking, duration: tracking.def().duration, countdown: 1.0 };
+        assert_eq!(runs, [static_clicking, static_clicking, static_clicking, precise_tracking]);
+        assert_eq!(playlist.runs(), 4);
+        assert_eq!(playlist.total_seconds(), 3.0 * 24.0 + tracking.def().duration + 1.0 + 4.0);
+    }
+
+    #[test]
+    fn shuffle_is_a_permutation_fixed_by_the_seed() {
+        let playlist = sample(true);
+        let schedule = |seed| playlist.schedule(&mut rng::stream(seed, Stream::Playlist, 0));
+        assert_eq!(schedule(7), schedule(7));
+        let orders: Vec<_> = (0..20).map(schedule).collect();
+        let unshuffled = sample(false).schedule(&mut rng::stream(0, Stream::Playlist, 0));
+        assert!(orders.iter().any(|order| *order != orders[0]), "some seed changes the order");
+        for order in &orders {
+            let mut sorted = order.clone();
+            sorted.sort_by(|a, b| b.countdown.total_cmp(&a.countdown));
+            assert_eq!(sorted, unshuffled);
+        }
+    }
+
+    #[test]
+    fn built_in_playlists_validate_and_unknown_names_fall_back() {
+        for (file, contents) in BUILT_IN {
+            let playlist: Playlist = toml::from_str(contents).unwrap_or_else(|err| panic!("{}: {}", file, err));
+            playlist.validate().unwrap_or_else(|err| panic!("{}: {}", file, err));
+        }
+        assert_eq!(find_or_default("warmup").name, "Warmup");
+        assert_eq!(find_or_default("no such playlist").name, DEFAULT_PLAYLIST);
+        assert_eq!(next(DEFAULT_PLAYLIST).name, registry()[1].name);
+    }
+}
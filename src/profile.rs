@@ -0,0 +1,319 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::PathBuf};
+use std::f32::consts::TAU;
+
+use crate::{curve::CurveModel, fov::{FovAxis, SensMatch}, games, playlist};
+
+const APP_DIR: &str = "NeuroCurveCalibration";
+const DEFAULT_PROFILE: &str = "default";
+const ACTIVE_PROFILE_FILE: &str = "active_profile";
+
+// Per-user settings that used to be compile-time constants
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    #[serde(skip)]
+    pub name: String,
+    pub sensitivity_cm_per_360: f32,
+    pub mouse_dpi: f32,
+    pub camera_fov: f32,       // Hipfire FOV in degrees, measured along fov_axis
+    pub fov_axis: FovAxis,
+    pub ads_fov: Option<f32>,  // Scoped/ADS FOV used while holding right mouse, along fov_axis
+    pub game: String,          // Game whose in-game sensitivity is shown alongside cm/360
+    pub game_fov: Option<f32>, // Hipfire FOV the cm/360 was tuned at, in the game's convention (default: game default)
+    pub sens_match: SensMatch,
+    pub curve: CurveModel,
+    pub crosshair: Crosshair,
+    pub keybinds: Keybinds,
+    pub playlist: String, // Playlist the next sequence runs
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Crosshair {
+    pub color: [f32; 3], // sRGB, 0-1
+    pub size: f32,       // Dot size in pixels
+}
+
+impl Default for Crosshair {
+    fn default() -> Self {
+        Self { color: [0.0, 1.0, 1.0], size: 2.0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keybinds {
+    pub start: KeyCode,
+    pub calibrate: KeyCode,
+    pub save_calibration: KeyCode,
+    pub fit_curve: KeyCode,
+    pub switch_profile: KeyCode,
+    pub switch_game: KeyCode,
+    pub switch_playlist: KeyCode,
+    pub release_cursor: KeyCode,
+    pub resume_session: KeyCode,       // Unfinished session prompt: resume it
+    pub save_partial_session: KeyCode, // Unfinished session prompt: save its completed scenarios to history
+    pub discard_session: KeyCode,      // Unfinished session prompt: discard it
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        Self {
+            start: KeyCode::Space,
+            calibrate: KeyCode::KeyC,
+            save_calibration: KeyCode::Enter,
+            fit_curve: KeyCode::KeyF,
+            switch_profile: KeyCode::F2,
+            switch_game: KeyCode::F3,
+            switch_playlist: KeyCode::F4,
+            release_cursor: KeyCode::Escape,
+            resume_session: KeyCode::KeyR,
+            save_partial_session: KeyCode::KeyP,
+            discard_session: KeyCode::KeyX,
+        }
+    }
+}
+
+// Key name as shown in the HUD, e.g. "C" rather than "KeyC"
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")).unwrap_or(&name).to_uppercase()
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE.to_string(),
+            sensitivity_cm_per_360: 10.0,
+            mouse_dpi: 1600.0,
+            camera_fov: 90.0,
+            fov_axis: FovAxis::Vertical,
+            ads_fov: None,
+            game: "valorant".to_string(),
+            game_fov: None,
+            sens_match: SensMatch::default(),
+            curve: CurveModel::default(),
+            crosshair: Crosshair::default(),
+            keybinds: Keybinds::default(),
+            playlist: playlist::DEFAULT_PLAYLIST.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    InvalidName(String),
+    InvalidValue { field: &'static str, value: f32, expected: &'static str },
+    InvalidCurve(String),
+    UnknownGame(String),
+    Overwrite(PathBuf, Box<ProfileError>),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            Self::Parse(path, err) => write!(f, "{}: {}", path.display(), err),
+            Self::InvalidName(name) => write!(f, "invalid profile name {:?} (use letters, digits, '-' or '_')", name),
+            Self::InvalidValue { field, value, expected } => write!(f, "{} = {} is invalid, expected {}", field, value, expected),
+            Self::InvalidCurve(reason) => write!(f, "invalid curve: {}", reason),
+            Self::UnknownGame(id) => write!(f, "unknown game {:?}, see --list-games", id),
+            Self::Overwrite(path, err) => write!(f, "not overwriting {}, it doesn't load ({}); fix or remove it first", path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl Profile {
+    // Radians of camera rotation per mouse count
+    pub fn radians_per_count(&self) -> f32 {
+        TAU / (self.sensitivity_cm_per_360 / 2.54 * self.mouse_dpi)
+    }
+
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        validate_name(&self.name)?;
+        check_range("sensitivity_cm_per_360", self.sensitivity_cm_per_360, 0.5, 500.0, "a value between 0.5 and 500 cm")?;
+        check_range("mouse_dpi", self.mouse_dpi, 50.0, 50000.0, "a value between 50 and 50000 DPI")?;
+        check_range("camera_fov", self.camera_fov, 10.0, 170.0, "a FOV between 10 and 170 degrees")?;
+        if let Some(ads_fov) = self.ads_fov {
+            check_range("ads_fov", ads_fov, 1.0, 170.0, "a FOV between 1 and 170 degrees")?;
+        }
+        if let Some(game_fov) = self.game_fov {
+            check_range("game_fov", game_fov, 10.0, 170.0, "a FOV between 10 and 170 degrees")?;
+        }
+        if let SensMatch::MonitorDistance { coefficient } = self.sens_match {
+            check_range("sens_match.coefficient", coefficient, 0.0, 10.0, "a monitor distance between 0 and 10")?;
+        }
+        check_range("crosshair.size", self.crosshair.size, 1.0, 64.0, "a size between 1 and 64 pixels")?;
+        for (channel, value) in ["crosshair.color.r", "crosshair.color.g", "crosshair.color.b"].into_iter().zip(self.crosshair.color) {
+            check_range(channel, value, 0.0, 1.0, "a color channel between 0 and 1")?;
+        }
+        if games::find(&self.game).is_none() {
+            return Err(ProfileError::UnknownGame(self.game.clone()));
+        }
+        self.curve.validate().map_err(ProfileError::InvalidCurve)
+    }
+
+    pub fn game(&self) -> &'static games::Game {
+        games::find(&self.game).unwrap_or(&games::GAMES[0])
+    }
+
+    pub fn path(name: &str) -> PathBuf {
+        profiles_dir().join(format!("{}.toml", name))
+    }
+
+    pub fn load(name: &str) -> Result<Self, ProfileError> {
+        validate_name(name)?;
+        let path = Self::path(name);
+        let contents = fs::read_to_string(&path).map_err(|e| ProfileError::Io(path.clone(), e))?;
+        let mut profile: Profile = toml::from_str(&contents).map_err(|e| ProfileError::Parse(path, e.to_string()))?;
+        profile.name = name.to_string();
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    pub fn save(&self) -> Result<(), ProfileError> {
+        self.validate()?;
+        let path = Self::path(&self.name);
+        // A file that doesn't load may only need fixing by hand, so it's never replaced
+        match Self::load(&self.name) {
+            Ok(_) => {},
+            Err(ProfileError::Io(_, err)) if err.kind() == io::ErrorKind::NotFound => {},
+            Err(err) => return Err(ProfileError::Overwrite(path, Box::new(err))),
+        }
+        let contents = toml::to_string_pretty(self).map_err(|e| ProfileError::Parse(path.clone(), e.to_string()))?;
+        fs::create_dir_all(profiles_dir()).map_err(|e| ProfileError::Io(profiles_dir(), e))?;
+        fs::write(&path, contents).map_err(|e| ProfileError::Io(path, e))
+    }
+
+    // Load a profile, creating it from the defaults if it doesn't exist yet
+    pub fn load_or_create(name: &str) -> Result<Self, ProfileError> {
+        validate_name(name)?;
+        if Self::path(name).exists() {
+            return Self::load(name);
+        }
+        let profile = Profile { name: name.to_string(), ..default() };
+        profile.save()?;
+        println!("Created profile '{}' at {}", name, Self::path(name).display());
+        Ok(profile)
+    }
+
+    // Names of all profiles in the config dir, sorted
+    pub fn list() -> Vec<String> {
+        let Ok(entries) = fs::read_dir(profiles_dir()) else { return Vec::new() };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+            .collect();
+        names.sort();
+        names
+    }
+
+    // Pick the profile for this run: the requested one wins, then the last active one
+    pub fn from_startup(requested: Option<String>) -> Self {
+        let name = requested.or_else(read_active_name).unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+
+        match Self::load_or_create(&name) {
+            Ok(profile) => {
+                write_active_name(&profile.name);
+                profile
+            },
+            // Keeps the name, so switching and saving still go to the requested profile, which save won't overwrite
+            // while it fails to load
+            Err(err) => {
+                eprintln!("Failed to load profile '{}': {}. Using built-in defaults.", name, err);
+                if validate_name(&name).is_ok() { Profile { name, ..default() } } else { Profile::default() }
+            }
+        }
+    }
+}
+
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir().unwrap_or_else(|| PathBuf::from(".")).join(APP_DIR)
+}
+
+// Recorded sessions and telemetry live apart from the settings
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir().unwrap_or_else(|| PathBuf::from(".")).join(APP_DIR)
+}
+
+pub fn profiles_dir() -> PathBuf {
+    config_dir().join("profiles")
+}
+
+fn read_active_name() -> Option<String> {
+    fs::read_to_string(config_dir().join(ACTIVE_PROFILE_FILE)).ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| validate_name(name).is_ok())
+}
+
+pub fn write_active_name(name: &str) {
+    if let Err(err) = fs::create_dir_all(config_dir()).and_then(|_| fs::write(config_dir().join(ACTIVE_PROFILE_FILE), name)) {
+        eprintln!("Failed to remember active profile: {}", err);
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), ProfileError> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid { Ok(()) } else { Err(ProfileError::InvalidName(name.to_string())) }
+}
+
+fn check_range(field: &'static str, value: f32, min: f32, max: f32, expected: &'static str) -> Result<(), ProfileError> {
+    if value.is_finite() && (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(ProfileError::InvalidValue { field, value, expected })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Classic;
+
+    #[test]
+    fn validate_rejects_out_of_range_values() {
+        assert!(Profile::default().validate().is_ok());
+        let invalid = [
+            Profile { name: "my profile".to_string(), ..default() },
+            Profile { sensitivity_cm_per_360: 0.0, ..default() },
+            Profile { mouse_dpi: f32::NAN, ..default() },
+            Profile { camera_fov: 180.0, ..default() },
+            Profile { ads_fov: Some(0.5), ..default() },
+            Profile { sens_match: SensMatch::MonitorDistance { coefficient: -1.0 }, ..default() },
+            Profile { crosshair: Crosshair { color: [0.0, 1.5, 0.0], ..default() }, ..default() },
+            Profile { game: "quake".to_string(), ..default() },
+            Profile { curve: CurveModel::Classic(Classic { acceleration: -1.0, ..default() }), ..default() },
+        ];
+        for profile in invalid {
+            assert!(profile.validate().is_err(), "{:?}", profile);
+        }
+    }
+
+    #[test]
+    fn toml_round_trip_keeps_every_setting() {
+        let profile = Profile {
+            sensitivity_cm_per_360: 34.5,
+            ads_fov: Some(40.0),
+            fov_axis: FovAxis::Horizontal,
+            sens_match: SensMatch::MonitorDistance { coefficient: 0.75 },
+            curve: CurveModel::Classic(Classic { offset: 2.0, acceleration: 0.05, exponent: 2.0, cap: 1.5 }),
+            keybinds: Keybinds { start: KeyCode::KeyG, ..default() },
+            ..default()
+        };
+        let mut parsed: Profile = toml::from_str(&toml::to_string_pretty(&profile).unwrap()).unwrap();
+        parsed.name = profile.name.clone();
+        assert_eq!(parsed, profile);
+
+        // Missing fields fall back to their defaults
+        let partial: Profile = toml::from_str("sensitivity_cm_per_360 = 20.0").unwrap();
+        assert_eq!(partial, Profile { sensitivity_cm_per_360: 20.0, ..default() });
+    }
+}
@@ -0,0 +1,379 @@
+use serde_json::{json, Map, Value};
+use std::{fmt, fs, io, path::Path};
+
+use crate::curve::{Classic, CurveModel, Linear, LookupTable, Natural, Power, SensitivityCurve};
+use crate::profile::{Profile, ProfileError};
+
+// Raw Accel settings.json layout this exporter targets
+const RAW_ACCEL_VERSION: &str = "1.6.1";
+const ACCEL_PARAMETERS: &str = "Whole or horizontal accel parameters";
+const VERTICAL_PARAMETERS: &str = "Vertical accel parameters";
+const SENS_MULTIPLIER: &str = "Sensitivity multiplier";
+const DPI: &str = "DPI (normalizes input speed unit: counts/ms -> in/s)";
+const WHOLE_ACCEL: &str = "Whole/combined accel (set false for 'by component' mode)";
+const DOMAIN_STRETCH: &str = "Stretches domain for horizontal vs vertical inputs";
+const RANGE_STRETCH: &str = "Stretches accel range for horizontal vs vertical inputs";
+const INPUT_SPEED_CAP: &str = "Input Speed Cap";
+const YX_RATIO: &str = "Y/X sensitivity ratio (vertical sens multiplier)";
+const GAIN_SAMPLES: usize = 64; // Points used when converting a gain-mode curve to a lookup table
+const GAIN_SAMPLE_MAX_SPEED: f32 = 100.0; // counts/ms
+
+#[derive(Debug)]
+pub enum RawAccelError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Unsupported(String),
+}
+
+impl fmt::Display for RawAccelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Json(err) => write!(f, "invalid settings.json: {}", err),
+            Self::Unsupported(reason) => write!(f, "unsupported Raw Accel settings: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RawAccelError {}
+
+impl From<io::Error> for RawAccelError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for RawAccelError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+// Write the profile's curve as a Raw Accel settings.json.
+// Raw Accel keeps its input unit at counts/ms (no DPI normalization) so curve speeds carry over unchanged.
+// Raw Accel scales counts before the game sees them, so the DPI only enters through `game_cm_per_360`: what the
+// game's own sensitivity gives at the profile's DPI. The sens multiplier scales it to the profile's cm/360 so the
+// curve's gain of 1 matches the calibrated sensitivity.
+pub fn export(profile: &Profile, game_cm_per_360: f32, path: &Path) -> Result<(), RawAccelError> {
+    if !game_cm_per_360.is_finite() || game_cm_per_360 <= 0.0 {
+        return Err(RawAccelError::Unsupported(format!("game cm/360 {}", game_cm_per_360)));
+    }
+    let mut multiplier = game_cm_per_360 / profile.sensitivity_cm_per_360;
+    let parameters = match &profile.curve {
+        CurveModel::Linear(curve) => {
+            multiplier *= curve.gain;
+            accel_parameters("noaccel", &[])
+        },
+        CurveModel::Classic(curve) => accel_parameters("classic", &[
+            ("inputOffset", json!(curve.offset)),
+            ("acceleration", json!(curve.acceleration)),
+            ("exponentClassic", json!(curve.exponent)),
+            ("Cap / Jump", json!({ "x": 0.0, "y": curve.cap })),
+        ]),
+        CurveModel::Natural(curve) => accel_parameters("natural", &[
+            ("inputOffset", json!(curve.offset)),
+            ("decayRate", json!(curve.decay_rate)),
+            ("limit", json!(curve.limit)),
+        ]),
+        CurveModel::Power(curve) => accel_parameters("power", &[
+            ("scale", json!(curve.scale)),
+            ("exponentPower", json!(curve.exponent)),
+            ("outputOffset", json!(curve.output_offset)),
+            ("Cap / Jump", json!({ "x": 0.0, "y": curve.cap })),
+        ]),
+        CurveModel::LookupTable(curve) => accel_parameters("lut", &[
+            ("data", json!(curve.points.iter().flatten().collect::<Vec<_>>())),
+        ]),
+    };
+
+    let settings = json!({
+        "### Accel Modes ###": "classic | jump | natural | synchronous | power | lut | noaccel",
+        "version": RAW_ACCEL_VERSION,
+        "defaultDeviceConfig": {
+            "disable": false,
+            "Use constant time interval based on polling rate": false,
+            DPI: 0,
+            "Polling rate Hz (keep at 0 for automatic adjustment)": 0
+        },
+        "profiles": [{
+            "name": profile.name,
+            WHOLE_ACCEL: true,
+            "lpNorm": 2.0,
+            DOMAIN_STRETCH: { "x": 1.0, "y": 1.0 },
+            RANGE_STRETCH: { "x": 1.0, "y": 1.0 },
+            ACCEL_PARAMETERS: parameters,
+            VERTICAL_PARAMETERS: accel_parameters("noaccel", &[]),
+            INPUT_SPEED_CAP: 0.0,
+            SENS_MULTIPLIER: multiplier,
+            YX_RATIO: 1.0,
+            "L/R sensitivity ratio (left sens multiplier)": 1.0,
+            "U/D sensitivity ratio (up sens multiplier)": 1.0,
+            "Degrees of rotation": 0.0,
+            "Degrees of angle snapping": 0.0
+        }],
+        "devices": []
+    });
+
+    fs::write(path, serde_json::to_string_pretty(&settings)?)?;
+    Ok(())
+}
+
+// Raw Accel's default parameter block with the given mode and overrides, always in velocity (sens) mode
+fn accel_parameters(mode: &str, overrides: &[(&str, Value)]) -> Value {
+    let mut parameters = json!({
+        "mode": mode,
+        "Gain / Velocity": false,
+        "inputOffset": 0.0,
+        "outputOffset": 0.0,
+        "acceleration": 0.005,
+        "decayRate": 0.1,
+        "gamma": 1.0,
+        "motivity": 1.5,
+        "exponentClassic": 2.0,
+        "scale": 1.0,
+        "exponentPower": 0.05,
+        "limit": 1.5,
+        "midpoint": 5.0,
+        "smooth": 0.5,
+        "Cap / Jump": { "x": 15.0, "y": 1.5 },
+        "Cap mode": "output",
+        "data": []
+    });
+    for (key, value) in overrides {
+        parameters[*key] = value.clone();
+    }
+    parameters
+}
+
+// Curve and sensitivity multiplier read from a Raw Accel settings.json
+#[derive(Debug, Clone)]
+pub struct RawAccelImport {
+    pub curve: CurveModel,
+    pub sens_multiplier: f32,
+}
+
+impl RawAccelImport {
+    // Make this the active curve, with the cm/360 the sens multiplier makes of the game's own sensitivity (the inverse
+    // of `export`). Leaves the profile untouched if the result is out of range.
+    pub fn apply_to(self, profile: &mut Profile, game_cm_per_360: f32) -> Result<(), ProfileError> {
+        let mut updated = profile.clone();
+        updated.sensitivity_cm_per_360 = game_cm_per_360 / self.sens_multiplier;
+        updated.curve = self.curve;
+        updated.validate()?;
+        *profile = updated;
+        Ok(())
+    }
+}
+
+// Read the first profile of a Raw Accel settings.json as a curve.
+// Settings that make it more than one curve over speed (by-component mode, stretches, ratios, input caps) are refused.
+pub fn import(path: &Path) -> Result<RawAccelImport, RawAccelError> {
+    let settings: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let profile = settings["profiles"].get(0)
+        .ok_or_else(|| RawAccelError::Unsupported("no profiles".to_string()))?;
+    check_single_curve(profile)?;
+    let dpi = input_dpi(&settings)?;
+    let parameters = profile[ACCEL_PARAMETERS].as_object()
+        .ok_or_else(|| RawAccelError::Unsupported(format!("missing \"{}\"", ACCEL_PARAMETERS)))?;
+
+    let sens_multiplier = profile[SENS_MULTIPLIER].as_f64().unwrap_or(1.0) as f32;
+    if sens_multiplier <= 0.0 {
+        return Err(RawAccelError::Unsupported(format!("sens multiplier {}", sens_multiplier)));
+    }
+
+    let number = |key: &str, default: f32| parameters.get(key).and_then(Value::as_f64).map_or(default, |v| v as f32);
+    let cap_mode = parameters.get("Cap mode").and_then(Value::as_str).unwrap_or("output");
+    if cap_mode != "output" {
+        return Err(RawAccelError::Unsupported(format!("cap mode \"{}\"", cap_mode)));
+    }
+    let cap = parameters.get("Cap / Jump").and_then(|cap| cap["y"].as_f64()).map_or(0.0, |v| v as f32);
+    let mode = parameters.get("mode").and_then(Value::as_str).unwrap_or("noaccel");
+
+    let curve = match mode {
+        "noaccel" => CurveModel::Linear(Linear::default()),
+        "classic" | "linear" => CurveModel::Classic(Classic {
+            offset: number("inputOffset", 0.0),
+            acceleration: number("acceleration", 0.005),
+            exponent: if mode == "linear" { 2.0 } else { number("exponentClassic", 2.0) },
+            cap,
+        }),
+        "natural" => CurveModel::Natural(Natural {
+            offset: number("inputOffset", 0.0),
+            decay_rate: number("decayRate", 0.1),
+            limit: number("limit", 1.5),
+        }),
+        "power" => CurveModel::Power(Power {
+            scale: number("scale", 1.0),
+            exponent: number("exponentPower", 0.05),
+            output_offset: number("outputOffset", 0.0),
+            cap,
+        }),
+        "lut" => CurveModel::LookupTable(lookup_table(parameters)?),
+        other => return Err(RawAccelError::Unsupported(format!("mode \"{}\"", other))),
+    };
+    let curve = if dpi > 0.0 { from_inches_per_second(curve, dpi) } else { curve };
+
+    // Gain mode describes the slope of output speed, so average it into the equivalent sensitivity curve
+    let gain_mode = parameters.get("Gain / Velocity").and_then(Value::as_bool).unwrap_or(false);
+    let curve = if gain_mode && mode != "noaccel" { gain_to_sensitivity(&curve) } else { curve };
+    curve.validate().map_err(RawAccelError::Unsupported)?;
+
+    Ok(RawAccelImport { curve, sens_multiplier })
+}
+
+// Whole mode with no stretches, ratio or input cap, so one curve over speed describes both axes.
+// The vertical parameters only apply in by-component mode.
+fn check_single_curve(profile: &Value) -> Result<(), RawAccelError> {
+    let unsupported = |setting: &str| Err(RawAccelError::Unsupported(setting.to_string()));
+    let number = |value: &Value, default: f64| value.as_f64().unwrap_or(default);
+    if !profile[WHOLE_ACCEL].as_bool().unwrap_or(true) {
+        return unsupported("by-component mode (separate horizontal and vertical curves)");
+    }
+    for key in [DOMAIN_STRETCH, RANGE_STRETCH] {
+        if number(&profile[key]["x"], 1.0) != 1.0 || number(&profile[key]["y"], 1.0) != 1.0 {
+            return unsupported(key);
+        }
+    }
+    if number(&profile[YX_RATIO], 1.0) != 1.0 {
+        return unsupported(YX_RATIO);
+    }
+    if number(&profile[INPUT_SPEED_CAP], 0.0) != 0.0 {
+        return unsupported(INPUT_SPEED_CAP);
+    }
+    Ok(())
+}
+
+// DPI Raw Accel normalizes input speeds by (0 for none), which every device config must share
+fn input_dpi(settings: &Value) -> Result<f32, RawAccelError> {
+    let dpi = |config: &Value| config[DPI].as_f64().unwrap_or(0.0) as f32;
+    let default = dpi(&settings["defaultDeviceConfig"]);
+    let devices = settings["devices"].as_array().map(Vec::as_slice).unwrap_or_default();
+    if default < 0.0 || devices.iter().any(|device| device["config"].is_object() && dpi(&device["config"]) != default) {
+        return Err(RawAccelError::Unsupported("per-device or negative DPI".to_string()));
+    }
+    Ok(default)
+}
+
+// Move a curve over in/s (Raw Accel with a DPI set) onto counts/ms, where 1 count/ms is 1000 / DPI in/s
+fn from_inches_per_second(curve: CurveModel, dpi: f32) -> CurveModel {
+    let inches_per_second = 1000.0 / dpi;
+    match curve {
+        CurveModel::Linear(curve) => CurveModel::Linear(curve),
+        CurveModel::Classic(curve) => CurveModel::Classic(Classic {
+            offset: curve.offset / inches_per_second,
+            acceleration: curve.acceleration * inches_per_second,
+            ..curve
+        }),
+        CurveModel::Natural(curve) => CurveModel::Natural(Natural {
+            offset: curve.offset / inches_per_second,
+            decay_rate: curve.decay_rate * inches_per_second,
+            ..curve
+        }),
+        CurveModel::Power(curve) => CurveModel::Power(Power { scale: curve.scale * inches_per_second, ..curve }),
+        CurveModel::LookupTable(curve) => CurveModel::LookupTable(LookupTable {
+            points: curve.points.into_iter().map(|[speed, gain]| [speed / inches_per_second, gain]).collect(),
+        }),
+    }
+}
+
+fn lookup_table(parameters: &Map<String, Value>) -> Result<LookupTable, RawAccelError> {
+    let data: Vec<f32> = parameters.get("data").and_then(Value::as_array)
+        .map(|data| data.iter().filter_map(Value::as_f64).map(|v| v as f32).collect())
+        .unwrap_or_default();
+    if data.is_empty() || !data.len().is_multiple_of(2) {
+        return Err(RawAccelError::Unsupported("lut data must be non-empty x,y pairs".to_string()));
+    }
+    Ok(LookupTable { points: data.chunks(2).map(|pair| [pair[0], pair[1]]).collect() })
+}
+
+// Sensitivity at speed v is the mean gain over 0..v
+fn gain_to_sensitivity(gain_curve: &dyn SensitivityCurve) -> CurveModel {
+    let step = GAIN_SAMPLE_MAX_SPEED / GAIN_SAMPLES as f32;
+    let mut area = 0.0;
+    let mut points = vec![[0.0, gain_curve.gain(0.0)]];
+    for i in 1..=GAIN_SAMPLES {
+        let (v0, v1) = ((i - 1) as f32 * step, i as f32 * step);
+        area += (gain_curve.gain(v0) + gain_curve.gain(v1)) / 2.0 * step;
+        points.push([v1, area / v1]);
+    }
+    CurveModel::LookupTable(LookupTable { points })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export_settings(curve: CurveModel, name: &str) -> (std::path::PathBuf, Value) {
+        let profile = Profile { sensitivity_cm_per_360: 30.0, curve, ..Profile::default() };
+        let path = std::env::temp_dir().join(format!("neurocurve_rawaccel_{}_{}.json", name, std::process::id()));
+        export(&profile, 60.0, &path).unwrap();
+        let settings = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        (path, settings)
+    }
+
+    fn reimport(path: &Path, settings: &Value) -> Result<RawAccelImport, RawAccelError> {
+        fs::write(path, settings.to_string()).unwrap();
+        let imported = import(path);
+        fs::remove_file(path).unwrap();
+        imported
+    }
+
+    #[test]
+    fn export_import_is_identity() {
+        let curves = [
+            CurveModel::Classic(Classic { offset: 1.5, acceleration: 0.02, exponent: 2.5, cap: 2.0 }),
+            CurveModel::Natural(Natural { offset: 0.5, decay_rate: 0.2, limit: 1.8 }),
+            CurveModel::Power(Power { scale: 0.5, exponent: 0.1, output_offset: 0.2, cap: 0.0 }),
+            CurveModel::LookupTable(LookupTable { points: vec![[1.0, 1.0], [5.0, 1.25], [20.0, 1.5]] }),
+        ];
+        for (i, curve) in curves.into_iter().enumerate() {
+            let (path, settings) = export_settings(curve.clone(), &i.to_string());
+            let imported = reimport(&path, &settings).unwrap();
+            assert_eq!(imported.curve, curve);
+            assert_eq!(imported.sens_multiplier, 2.0);
+        }
+    }
+
+    #[test]
+    fn linear_gain_folds_into_multiplier() {
+        let (path, settings) = export_settings(CurveModel::Linear(Linear { gain: 1.5 }), "linear");
+        let imported = reimport(&path, &settings).unwrap();
+        assert_eq!(imported.curve, CurveModel::Linear(Linear::default()));
+        assert_eq!(imported.sens_multiplier, 3.0);
+    }
+
+    #[test]
+    fn apply_restores_the_exported_profile() {
+        let curve = CurveModel::Classic(Classic { offset: 1.5, acceleration: 0.02, exponent: 2.5, cap: 2.0 });
+        let (path, settings) = export_settings(curve.clone(), "apply");
+        let mut profile = Profile::default();
+        reimport(&path, &settings).unwrap().apply_to(&mut profile, 60.0).unwrap();
+        assert_eq!((profile.sensitivity_cm_per_360, &profile.curve), (30.0, &curve));
+
+        // A linear gain comes back as the cm/360 it amounts to
+        let (path, settings) = export_settings(CurveModel::Linear(Linear { gain: 1.5 }), "apply_linear");
+        reimport(&path, &settings).unwrap().apply_to(&mut profile, 60.0).unwrap();
+        assert_eq!((profile.sensitivity_cm_per_360, &profile.curve), (20.0, &CurveModel::Linear(Linear::default())));
+    }
+
+    #[test]
+    fn dpi_normalized_speeds_convert_to_counts_per_ms() {
+        let table = LookupTable { points: vec![[10.0, 1.0], [20.0, 1.5]] };
+        let (path, mut settings) = export_settings(CurveModel::LookupTable(table), "dpi");
+        settings["defaultDeviceConfig"][DPI] = json!(800);
+        // 1 count/ms at 800 DPI is 1.25 in/s
+        let imported = reimport(&path, &settings).unwrap();
+        assert_eq!(imported.curve, CurveModel::LookupTable(LookupTable { points: vec![[8.0, 1.0], [16.0, 1.5]] }));
+    }
+
+    #[test]
+    fn rejects_settings_beyond_one_curve() {
+        let overrides = [(WHOLE_ACCEL, json!(false)), (YX_RATIO, json!(0.8)), (INPUT_SPEED_CAP, json!(50.0)),
+                         (DOMAIN_STRETCH, json!({ "x": 1.0, "y": 2.0 }))];
+        for (i, (key, value)) in overrides.into_iter().enumerate() {
+            let (path, mut settings) = export_settings(CurveModel::default(), &format!("reject{}", i));
+            settings["profiles"][0][key] = value;
+            assert!(matches!(reimport(&path, &settings), Err(RawAccelError::Unsupported(_))), "{}", key);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use bevy_fps_controller::controller::RenderPlayer;
+use serde::{Deserialize, Serialize};
+use std::{fs, io::{self, BufWriter, Write}, path::PathBuf};
+
+use crate::{flick, fov, scoring::SessionResults, telemetry::MotionRecorder, ScenarioEvent, Target, TargetTimeline};
+
+const CLOSE_RADII: f32 = 3.0; // Crosshair within this many target radii counts as close
+
+// Milestones of one target's life, in seconds since startup
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TargetReaction {
+    pub spawned: f32,
+    pub started: Option<f32>, // Once in view with the previous target cleared, None if the crosshair never came close
+    pub first_close: Option<f32>,
+    pub first_on_target: Option<f32>,
+    pub killed: Option<f32>, // None for targets still alive when the scenario ended
+}
+
+impl TargetReaction {
+    // Becoming the next target until the crosshair first came close
+    pub fn reaction_latency(&self) -> Option<f32> {
+        Some(self.first_close? - self.started?)
+    }
+
+    // First close until first on target
+    pub fn acquisition_time(&self) -> Option<f32> {
+        Some(self.first_on_target? - self.first_close?)
+    }
+
+    // First on target until the kill
+    pub fn confirmation_delay(&self) -> Option<f32> {
+        Some(self.killed? - self.first_on_target?)
+    }
+
+    pub fn time_to_kill(&self) -> Option<f32> {
+        self.killed.map(|killed| killed - self.spawned)
+    }
+}
+
+impl TargetTimeline {
+    pub fn finish(&self, killed: Option<f32>) -> TargetReaction {
+        TargetReaction { spawned: self.spawned, started: self.started, first_close: self.first_close, first_on_target: self.first_on_target, killed }
+    }
+}
+
+// Mean of the metric over the targets that reached it
+pub fn mean(reactions: &[TargetReaction], metric: fn(&TargetReaction) -> Option<f32>) -> Option<f32> {
+    let values: Vec<f32> = reactions.iter().filter_map(metric).collect();
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
+
+impl TargetTimeline {
+    // Advance by one frame with the crosshair `offset` from the target. The latency clock starts when the target is
+    // in view and the previous target has been cleared, so time spent shooting other targets isn't counted against it.
+    fn observe(&mut self, now: f32, offset: Vec2, radius: f32, half_fov: Vec2, last_kill: Option<f32>) {
+        if self.visible.is_none() && offset.x.abs() <= half_fov.x && offset.y.abs() <= half_fov.y {
+            self.visible = Some(now);
+        }
+        if offset.length() <= radius * CLOSE_RADII && self.first_close.is_none() {
+            self.first_close = Some(now);
+            self.started = Some(self.visible.unwrap_or(now).max(last_kill.unwrap_or(self.spawned)));
+        }
+        if offset.length() <= radius {
+            self.first_on_target = Some(now);
+        }
+    }
+}
+
+// Stamp the first frame each target is in view, the crosshair comes close to it and lands on it
+pub fn update_target_timelines(time: Res<Time>, results: Res<SessionResults>,
+                               camera: Query<(&Transform, &Projection), With<RenderPlayer>>,
+                               mut targets: Query<(&Transform, &Target, &mut TargetTimeline)>) {
+    let Ok((camera_transform, projection)) = camera.get_single() else { return };
+    let half_fov = match projection {
+        Projection::Perspective(perspective) =>
+            Vec2::new(fov::horizontal_fov(perspective.fov, perspective.aspect_ratio), perspective.fov) / 2.0,
+        _ => Vec2::splat(std::f32::consts::PI),
+    };
+    let last_kill = results.current.reactions.iter().rev().find_map(|reaction| reaction.killed);
+    let now = time.elapsed_secs();
+
+    for (transform, target, mut timeline) in &mut targets {
+        if timeline.first_on_target.is_some() {
+            continue;
+        }
+        let distance = transform.translation.distance(camera_transform.translation);
+        let radius = (target.radius / distance.max(target.radius)).asin();
+        timeline.observe(now, flick::angular_offset(camera_transform, transform.translation), radius, half_fov, last_kill);
+    }
+}
+
+// Save the ended scenario's reaction timelines, survivors included, with the session's telemetry
+pub fn save_reactions(mut events: EventReader<ScenarioEvent>, results: Res<SessionResults>, recorder: Res<MotionRecorder>) {
+    for event in events.read() {
+        let ScenarioEvent::Ended { index, scenario } = *event else { continue };
+        let Some(card) = results.completed.last() else { continue };
+
+        let mean_ms = |metric| mean(&card.reactions, metric).map_or("-".to_string(), |t| format!("{:.0}ms", t * 1000.0));
+        println!("{:?} reactions: latency {} | acquisition {} | confirmation {} ({} targets)", scenario,
+                 mean_ms(TargetReaction::reaction_latency), mean_ms(TargetReaction::acquisition_time),
+                 mean_ms(TargetReaction::confirmation_delay), card.reactions.len());
+
+        match write_csv(recorder.session_dir().join(format!("{:02}_{:?}_targets.csv", index, scenario)), &card.reactions) {
+            Ok(path) => println!("Saved target timelines to {}", path.display()),
+            Err(err) => eprintln!("Failed to save target timelines: {}", err),
+        }
+    }
+}
+
+fn write_csv(path: PathBuf, reactions: &[TargetReaction]) -> io::Result<PathBuf> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let optional = |value: Option<f32>| value.map_or(String::new(), |value| format!("{:.4}", value));
+    let mut out = BufWriter::new(fs::File::create(&path)?);
+    writeln!(out, "spawned,started,first_close,first_on_target,killed,reaction_latency,acquisition_time,confirmation_delay")?;
+    for r in reactions {
+        writeln!(out, "{:.4},{},{},{},{},{},{},{}", r.spawned, optional(r.started), optional(r.first_close), optional(r.first_on_target),
+                 optional(r.killed), optional(r.reaction_latency()), optional(r.acquisition_time()),
+                 optional(r.confirmation_delay()))?;
+    }
+    out.flush()?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF_FOV: Vec2 = Vec2::new(0.8, 0.5);
+    const RADIUS: f32 = 0.02;
+
+    fn timeline(spawned: f32) -> TargetTimeline {
+        TargetTimeline { spawned, ..default() }
+    }
+
+    #[test]
+    fn latency_starts_when_the_previous_target_is_cleared() {
+        // Spawned in view at 1s while the player was still shooting a target killed at 1.5s
+        let mut timeline = timeline(1.0);
+        timeline.observe(1.0, Vec2::new(0.3, 0.0), RADIUS, HALF_FOV, Some(0.5));
+        timeline.observe(1.5, Vec2::new(0.3, 0.0), RADIUS, HALF_FOV, Some(0.5));
+        timeline.observe(1.7, Vec2::new(0.05, 0.0), RADIUS, HALF_FOV, Some(1.5));
+        timeline.observe(1.8, Vec2::new(0.01, 0.0), RADIUS, HALF_FOV, Some(1.5));
+        let reaction = timeline.finish(Some(2.0));
+        assert_eq!(reaction.started, Some(1.5));
+        assert!((reaction.reaction_latency().unwrap() - 0.2).abs() < 1e-6);
+        assert!((reaction.acquisition_time().unwrap() - 0.1).abs() < 1e-6);
+        assert!((reaction.time_to_kill().unwrap() - 1.0).abs() < 1e-6, "time to kill still counts from the spawn");
+    }
+
+    #[test]
+    fn latency_starts_when_the_target_enters_the_view() {
+        // Spawned behind the player at 1s, turned into view at 1.4s
+        let mut timeline = timeline(1.0);
+        timeline.observe(1.0, Vec2::new(2.5, 0.0), RADIUS, HALF_FOV, None);
+        timeline.observe(1.2, Vec2::new(1.0, 0.0), RADIUS, HALF_FOV, None);
+        timeline.observe(1.4, Vec2::new(0.6, 0.1), RADIUS, HALF_FOV, None);
+        timeline.observe(1.6, Vec2::new(0.0, 0.03), RADIUS, HALF_FOV, None);
+        let reaction = timeline.finish(None);
+        assert_eq!((reaction.started, reaction.first_close, reaction.first_on_target), (Some(1.4), Some(1.6), None));
+        assert!((reaction.reaction_latency().unwrap() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lone_targets_in_view_time_from_their_spawn() {
+        let mut timeline = timeline(3.0);
+        timeline.observe(3.0, Vec2::new(0.2, 0.2), RADIUS, HALF_FOV, Some(2.9));
+        timeline.observe(3.25, Vec2::new(0.01, 0.0), RADIUS, HALF_FOV, Some(2.9));
+        let reaction = timeline.finish(Some(3.3));
+        assert_eq!((reaction.started, reaction.first_close, reaction.first_on_target), (Some(3.0), Some(3.25), Some(3.25)));
+        assert_eq!(reaction.reaction_latency(), Some(0.25));
+        assert_eq!(reaction.acquisition_time(), Some(0.0));
+    }
+
+    #[test]
+    fn means_skip_targets_that_never_reached_the_metric() {
+        let reached = TargetReaction { spawned: 0.0, started: Some(0.0), first_close: Some(0.3), first_on_target: None, killed: None };
+        let survivor = TargetReaction { spawned: 0.0, started: None, first_close: None, first_on_target: None, killed: None };
+        assert_eq!(mean(&[reached, survivor], TargetReaction::reaction_latency), Some(0.3));
+        assert_eq!(mean(&[survivor], TargetReaction::reaction_latency), None);
+    }
+}
@@ -0,0 +1,251 @@
+use bevy::prelude::*;
+use bevy_fps_controller::controller::FpsController;
+use std::{collections::HashMap, fs, io::{self, BufWriter, Write}, path::PathBuf};
+
+use crate::{aim_profile::AimProfile, calibration::{self, Calibration}, fitts, flick,
+            history::{ScenarioMetrics, SessionRecord}, profile::Profile, scoring::{ScoreCard, SessionResults},
+            set_cursor_state, start_scenario_sequence, telemetry::MotionRecorder, ScenarioEvent, ScenarioState,
+            ScenarioType, Target};
+
+const COLUMNS: [&str; 8] = ["Scenario", "Score", "vs prev", "Best", "Accuracy", "TTK", "Throughput", "Flick endpoint"];
+const BUTTON_COLOR: Color = Color::srgb(0.2, 0.2, 0.3);
+const BUTTON_HOVER_COLOR: Color = Color::srgb(0.3, 0.3, 0.45);
+const CHART_WIDTH: f32 = 300.0; // Pixels for a full 100 bar
+
+// Metrics and aim profile of the previous completed sequence and the best score seen per scenario
+#[derive(Resource, Default)]
+pub struct SessionRecords {
+    pub previous: Vec<ScenarioMetrics>,
+    pub previous_profile: Option<AimProfile>,
+    pub bests: HashMap<ScenarioType, i32>,
+}
+
+impl SessionRecords {
+    // Make this sequence the previous one and raise any beaten bests
+    pub fn record(&mut self, cards: &[ScoreCard]) {
+        for card in cards {
+            let Some(scenario) = card.scenario else { continue };
+            let best = self.bests.entry(scenario).or_insert(card.score());
+            *best = (*best).max(card.score());
+        }
+        self.previous = cards.iter().filter_map(ScenarioMetrics::from_card).collect();
+        self.previous_profile = Some(AimProfile::from_cards(cards));
+    }
+
+    // Previous sequence and personal bests from the stored history, ignoring calibration blocks for the former
+    pub fn from_history(records: &[SessionRecord]) -> Self {
+        let mut session_records = Self::default();
+        for record in records {
+            for metrics in &record.scenarios {
+                let best = session_records.bests.entry(metrics.scenario).or_insert(metrics.score);
+                *best = (*best).max(metrics.score);
+            }
+        }
+        if let Some(previous) = records.iter().rev().find(|record| !record.calibration_block) {
+            session_records.previous = previous.scenarios.clone();
+            session_records.previous_profile = Some(previous.aim_profile.clone());
+        }
+        session_records
+    }
+}
+
+// Root of the results screen, holding the rows it shows so Save writes exactly what was on screen
+#[derive(Component)]
+pub struct ResultsScreen {
+    rows: Vec<[String; 8]>,
+    adjusted_cm: f32,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsButton {
+    Retry,
+    Save,
+    Calibrate,
+}
+
+fn row(card: &ScoreCard, records: &SessionRecords) -> [String; 8] {
+    let previous = records.previous.iter().find(|previous| Some(previous.scenario) == card.scenario);
+    let best = card.scenario.and_then(|scenario| records.bests.get(&scenario));
+    let accuracy = if card.is_tracking() { card.time_on_target() } else { card.accuracy() };
+    [
+        card.scenario.map_or("Free play".to_string(), |scenario| format!("{:?}", scenario)),
+        card.score().to_string(),
+        previous.map_or("-".to_string(), |previous| format!("{:+}", card.score() - previous.score)),
+        match best {
+            Some(&best) if card.score() > best => format!("{} (new)", card.score()),
+            Some(best) => best.to_string(),
+            None => format!("{} (new)", card.score()),
+        },
+        format!("{:.0}%", accuracy * 100.0),
+        card.mean_time_to_kill().map_or("-".to_string(), |ttk| format!("{:.2}s", ttk)),
+        fitts::fit(&card.fitts).map_or("-".to_string(), |fit| format!("{:.2} bits/s", fit.throughput)),
+        flick::endpoint_bias(&card.flicks).map_or("-".to_string(), |(bias, _)| format!("{:+.1}%", bias * 100.0)),
+    ]
+}
+
+// cm/360 that the sequence's flick endpoints suggest, used as the centre of a calibration started from the screen
+fn adjusted_cm_per_360(profile: &Profile, cards: &[ScoreCard]) -> f32 {
+    flick::endpoint_bias(cards.iter().flat_map(|card| &card.flicks))
+        .and_then(|(bias, flicks)| calibration::unbiased_cm_per_360(&[(profile.sensitivity_cm_per_360, bias, flicks)]))
+        .unwrap_or(profile.sensitivity_cm_per_360)
+}
+
+fn spawn_screen(commands: &mut Commands, rows: Vec<[String; 8]>, aim_profile: &AimProfile,
+                previous_profile: Option<&AimProfile>, adjusted_cm: f32) {
+    commands.spawn((
+        ResultsScreen { rows: rows.clone(), adjusted_cm },
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0), height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column, align_items: AlignItems::Center, justify_content: JustifyContent::Center,
+            row_gap: Val::Px(20.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.05, 0.85)),
+    )).with_children(|screen| {
+        screen.spawn((Text::new("Session results"), TextFont { font_size: 32.0, ..default() }));
+
+        // One grid cell per metric, header row first
+        screen.spawn(Node {
+            display: Display::Grid,
+            grid_template_columns: RepeatedGridTrack::auto(COLUMNS.len() as u16),
+            column_gap: Val::Px(24.0), row_gap: Val::Px(6.0),
+            ..default()
+        }).with_children(|table| {
+            for header in COLUMNS {
+                table.spawn((Text::new(header), TextColor(Color::srgb(0.6, 0.8, 1.0))));
+            }
+            for cell in rows.into_iter().flatten() {
+                table.spawn(Text::new(cell));
+            }
+        });
+
+        // Bar chart of the category and sub-skill scores, with the change since the previous sequence
+        let overall = aim_profile.overall().map_or(String::new(), |overall| format!(": {:.0}", overall));
+        screen.spawn(Text::new(format!("Aim profile{}", overall)));
+        screen.spawn(Node { flex_direction: FlexDirection::Column, row_gap: Val::Px(4.0), ..default() }).with_children(|chart| {
+            for (label, score) in aim_profile.entries() {
+                let change = previous_profile
+                    .and_then(|previous| previous.entries().into_iter().find(|(previous, _)| *previous == label))
+                    .map_or(String::new(), |(_, previous)| format!(" ({:+.0})", score - previous));
+                chart.spawn(Node { align_items: AlignItems::Center, column_gap: Val::Px(8.0), ..default() }).with_children(|bar| {
+                    bar.spawn((Text::new(label), Node { width: Val::Px(110.0), ..default() }));
+                    bar.spawn((Node { width: Val::Px(CHART_WIDTH), height: Val::Px(14.0), ..default() },
+                               BackgroundColor(Color::srgb(0.15, 0.15, 0.2))))
+                        .with_children(|track| {
+                            track.spawn((Node { width: Val::Percent(score), height: Val::Percent(100.0), ..default() },
+                                         BackgroundColor(Color::srgb(0.0, 0.8, 0.8))));
+                        });
+                    bar.spawn(Text::new(format!("{:.0}{}", score, change)));
+                });
+            }
+        });
+
+        screen.spawn(Node { column_gap: Val::Px(16.0), ..default() }).with_children(|buttons| {
+            for (button, label) in [(ResultsButton::Retry, "Retry".to_string()), (ResultsButton::Save, "Save".to_string()),
+                                    (ResultsButton::Calibrate, format!("Calibrate around {:.1} cm/360", adjusted_cm))] {
+                buttons.spawn((button, Button, BackgroundColor(BUTTON_COLOR),
+                               Node { padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)), ..default() }))
+                    .with_children(|button| { button.spawn(Text::new(label)); });
+            }
+        });
+    });
+}
+
+// Write the sequence's metrics table as CSV next to its telemetry
+fn save_summary(rows: &[[String; 8]], dir: PathBuf) -> io::Result<PathBuf> {
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("summary.csv");
+    let mut out = BufWriter::new(fs::File::create(&path)?);
+    writeln!(out, "{}", COLUMNS.join(","))?;
+    for row in rows {
+        writeln!(out, "{}", row.join(","))?;
+    }
+    out.flush()?;
+    Ok(path)
+}
+
+// Open the results screen once a full sequence (not a calibration block) completes
+pub fn show_results_screen(
+    mut events: EventReader<ScenarioEvent>,
+    mut commands: Commands,
+    calibration: Res<Calibration>,
+    results: Res<SessionResults>,
+    profile: Res<Profile>,
+    mut records: ResMut<SessionRecords>,
+    mut window_query: Query<&mut Window>,
+    mut controller_query: Query<&mut FpsController>,
+) {
+    if !events.read().any(|event| matches!(event, ScenarioEvent::Completed)) || calibration.is_running() {
+        return;
+    }
+
+    // Rows compare against the records as they were before this sequence
+    let rows = results.completed.iter().map(|card| row(card, &records)).collect();
+    let aim_profile = AimProfile::from_cards(&results.completed);
+    spawn_screen(&mut commands, rows, &aim_profile, records.previous_profile.as_ref(),
+                 adjusted_cm_per_360(&profile, &results.completed));
+    records.record(&results.completed);
+    if let (Ok(mut window), Ok(mut controller)) = (window_query.get_single_mut(), controller_query.get_single_mut()) {
+        set_cursor_state(&mut window, &mut controller, false);
+    }
+}
+
+// Retry the sequence, save the table, or calibrate around the adjusted sensitivity
+pub fn handle_results_buttons(
+    mut commands: Commands,
+    mut interactions: Query<(&Interaction, &ResultsButton, &mut BackgroundColor), Changed<Interaction>>,
+    screen: Query<(Entity, &ResultsScreen)>,
+    mut scenario_state: ResMut<ScenarioState>,
+    mut results: ResMut<SessionResults>,
+    mut calibration: ResMut<Calibration>,
+    mut profile: ResMut<Profile>,
+    recorder: Res<MotionRecorder>,
+    targets: Query<Entity, With<Target>>,
+    mut window_query: Query<&mut Window>,
+    mut controller_query: Query<&mut FpsController>,
+) {
+    let Ok((screen, ResultsScreen { rows, adjusted_cm })) = screen.get_single() else { return };
+    for (interaction, &button, mut color) in &mut interactions {
+        match interaction {
+            Interaction::Hovered => *color = BackgroundColor(BUTTON_HOVER_COLOR),
+            Interaction::None => *color = BackgroundColor(BUTTON_COLOR),
+            Interaction::Pressed => {},
+        }
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if button == ResultsButton::Save {
+            match save_summary(rows, recorder.session_dir()) {
+                Ok(path) => println!("Saved session results to {}", path.display()),
+                Err(err) => eprintln!("Failed to save session results: {}", err),
+            }
+            continue;
+        }
+
+        // Retry and Calibrate both leave the screen and start a new sequence
+        commands.entity(screen).despawn_recursive();
+        if button == ResultsButton::Calibrate {
+            calibration::start(&mut calibration, *adjusted_cm, &mut scenario_state, &mut profile, &mut results, &mut commands, &targets);
+        } else {
+            let playlist = scenario_state.playlist;
+            start_scenario_sequence(&mut scenario_state, &mut results, &mut commands, &targets, playlist, None);
+        }
+        if let (Ok(mut window), Ok(mut controller)) = (window_query.get_single_mut(), controller_query.get_single_mut()) {
+            set_cursor_state(&mut window, &mut controller, true);
+        }
+        return;
+    }
+}
+
+// Close the screen when a sequence is started some other way (Space or C)
+pub fn hide_results_screen(mut commands: Commands, scenario_state: Res<ScenarioState>,
+                           screen: Query<Entity, With<ResultsScreen>>) {
+    if !scenario_state.has_started {
+        return;
+    }
+    for entity in &screen {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, fmt, sync::OnceLock};
+
+use crate::{aim_profile::{Category, Skill}, definitions, profile, TARGET_SIZE};
+
+const DEFAULT_DURATION: f32 = 30.0; // Seconds, unless a playlist entry overrides it
+
+// Built-in drills; `<config>/scenarios/*.toml` adds more or overrides them (see definitions::load_dir)
+const BUILT_IN: &[(&str, &str)] = &[
+    ("dynamic_clicking.toml", include_str!("../scenarios/dynamic_clicking.toml")),
+    ("static_clicking.toml", include_str!("../scenarios/static_clicking.toml")),
+    ("linear_clicking.toml", include_str!("../scenarios/linear_clicking.toml")),
+    ("precise_tracking.toml", include_str!("../scenarios/precise_tracking.toml")),
+    ("reactive_tracking.toml", include_str!("../scenarios/reactive_tracking.toml")),
+    ("control_tracking.toml", include_str!("../scenarios/control_tracking.toml")),
+    ("speed_switching.toml", include_str!("../scenarios/speed_switching.toml")),
+    ("evasive_switching.toml", include_str!("../scenarios/evasive_switching.toml")),
+    ("stability_switching.toml", include_str!("../scenarios/stability_switching.toml")),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MovementPattern {
+    #[default]
+    Static,   // No movement
+    Linear,   // Simple linear movement with bouncing
+    Circular, // Circular or figure-8 patterns
+    Random,   // Random movement with direction changes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Movement {
+    pub pattern: MovementPattern,
+    pub speed: f32,         // Units/s
+    pub radius: f32,        // Circular: radius of the loop
+    pub turn_interval: f32, // Random: seconds between direction changes
+    pub evasive: bool,      // Random: also sidestep relative to the player
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Self { pattern: MovementPattern::Static, speed: 0.0, radius: 15.0, turn_interval: 2.0, evasive: false }
+    }
+}
+
+// Where a scenario's targets appear
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum SpawnRegion {
+    Points { positions: Vec<Vec3> }, // Target i at position i, wrapping around; respawns pick one at random
+    Box { min: Vec3, max: Vec3 },    // Uniformly random within the box
+}
+
+impl SpawnRegion {
+    pub fn position(&self, index: Option<usize>, rng: &mut impl Rng) -> Vec3 {
+        match self {
+            Self::Points { positions } => {
+                let index = index.unwrap_or_else(|| rng.random_range(0..positions.len()));
+                positions[index % positions.len()]
+            },
+            Self::Box { min, max } => Vec3::new(rng.random_range(min.x..=max.x), rng.random_range(min.y..=max.y),
+                                                rng.random_range(min.z..=max.z)),
+        }
+    }
+}
+
+// What replaces a killed target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Respawn {
+    Random,  // A free-play target with a random pattern anywhere in view
+    Replace, // Another target from this scenario's spawn region and movement
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scoring {
+    Clicks,   // Hits and misses per click
+    Tracking, // Time on target while the trigger is held; the target never dies
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioDef {
+    #[serde(default)]
+    pub name: String, // Defaults to the file name
+    pub category: Category,
+    pub scoring: Scoring,
+    pub skills: BTreeMap<Skill, f32>, // How much the result counts towards each sub-skill
+    #[serde(default = "default_duration")]
+    pub duration: f32,
+    #[serde(default = "default_target_size")]
+    pub target_size: f32, // Radius
+    pub count: usize,
+    pub spawn: SpawnRegion,
+    #[serde(default)]
+    pub movement: Movement,
+    pub respawn: Respawn,
+    #[serde(default)]
+    pub refill_chance: f32, // Chance per fixed tick (64 Hz) to top up to `count` when targets are missing
+}
+
+fn default_duration() -> f32 {
+    DEFAULT_DURATION
+}
+
+fn default_target_size() -> f32 {
+    TARGET_SIZE
+}
+
+impl ScenarioDef {
+    pub fn validate(&self) -> Result<(), String> {
+        let finite = |values: &[f32]| values.iter().all(|v| v.is_finite());
+        if self.name.is_empty() || !self.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid name {:?} (use letters, digits or '_')", self.name));
+        }
+        if !finite(&[self.duration]) || self.duration <= 0.0 {
+            return Err("duration must be positive".into());
+        }
+        if !finite(&[self.target_size]) || !(0.1..=20.0).contains(&self.target_size) {
+            return Err("target_size must be between 0.1 and 20".into());
+        }
+        if self.count == 0 || self.count > 100 {
+            return Err("count must be between 1 and 100".into());
+        }
+        if self.skills.is_empty() || self.skills.values().any(|&weight| !finite(&[weight]) || weight <= 0.0) {
+            return Err("skills needs at least one positive weight".into());
+        }
+        if !finite(&[self.refill_chance]) || !(0.0..=1.0).contains(&self.refill_chance) {
+            return Err("refill_chance must be between 0 and 1".into());
+        }
+        let m = &self.movement;
+        if !finite(&[m.speed, m.radius, m.turn_interval]) || m.speed < 0.0 || m.radius <= 0.0 || m.turn_interval <= 0.0 {
+            return Err("movement needs speed >= 0, radius > 0 and turn_interval > 0".into());
+        }
+        match &self.spawn {
+            SpawnRegion::Points { positions } if positions.is_empty() => Err("spawn.positions is empty".into()),
+            SpawnRegion::Points { positions } if !positions.iter().all(|p| p.is_finite()) => Err("spawn.positions must be finite".into()),
+            SpawnRegion::Box { min, max } if !min.is_finite() || !max.is_finite() || min.cmpgt(*max).any() =>
+                Err("spawn.min must not exceed spawn.max".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn load() -> Vec<ScenarioDef> {
+    definitions::load_dir("scenario", BUILT_IN, &profile::config_dir().join("scenarios"), |def| &mut def.name, ScenarioDef::validate)
+}
+
+// Every scenario definition, built-in ones first, loaded on first use
+pub fn registry() -> &'static [ScenarioDef] {
+    static REGISTRY: OnceLock<Vec<ScenarioDef>> = OnceLock::new();
+    REGISTRY.get_or_init(load)
+}
+
+// A scenario in the registry. Shown and stored by name, so history and journals survive reordering.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScenarioType(usize);
+
+impl ScenarioType {
+    pub fn all() -> Vec<ScenarioType> {
+        (0..registry().len()).map(ScenarioType).collect()
+    }
+
+    pub fn find(name: &str) -> Option<ScenarioType> {
+        registry().iter().position(|def| def.name.eq_ignore_ascii_case(name)).map(ScenarioType)
+    }
+
+    pub fn def(self) -> &'static ScenarioDef {
+        &registry()[self.0]
+    }
+
+    // Scored continuously on time on target instead of per click, with a target that never dies
+    pub fn is_tracking(self) -> bool {
+        self.def().scoring == Scoring::Tracking
+    }
+
+    // Discrete pointing at one target per kill, where Fitts's law applies
+    pub fn is_clicking(self) -> bool {
+        self.def().category == Category::Clicking
+    }
+}
+
+impl fmt::Debug for ScenarioType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.def().name)
+    }
+}
+
+impl Serialize for ScenarioType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.def().name)
+    }
+}
+
+impl<'de> Deserialize<'de> for ScenarioType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        ScenarioType::find(&name).ok_or_else(|| de::Error::custom(format!("unknown scenario {:?}", name)))
+    }
+}
+
+// `--list-scenarios` prints the registry and exits
+pub fn run_cli() -> bool {
+    if !std::env::args().any(|arg| arg == "--list-scenarios") {
+        return false;
+    }
+    for def in registry() {
+        println!("{:<20} {:?}/{:?} {} x {:?} at {:.0} u/s, {:.0}s", def.name, def.category, def.scoring, def.count,
+                 def.movement.pattern, def.movement.speed, def.duration);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn built_in(file: &str) -> &'static str {
+        BUILT_IN.iter().find(|(name, _)| *name == file).unwrap().1
+    }
+
+    #[test]
+    fn every_built_in_scenario_parses_and_validates() {
+        let paths: Vec<_> = std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/scenarios")).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|file| file.ends_with(".toml"))
+            .collect();
+        assert_eq!(paths.len(), BUILT_IN.len(), "every file in scenarios/ is built in");
+        for (file, contents) in BUILT_IN {
+            let def: ScenarioDef = toml::from_str(contents).unwrap_or_else(|err| panic!("{}: {}", file, err));
+            def.validate().unwrap_or_else(|err| panic!("{}: {}", file, err));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_broken_definitions() {
+        let def: ScenarioDef = toml::from_str(built_in("static_clicking.toml")).unwrap();
+        let broken = [
+            ScenarioDef { name: "Static Clicking".to_string(), ..def.clone() },
+            ScenarioDef { duration: 0.0, ..def.clone() },
+            ScenarioDef { target_size: 50.0, ..def.clone() },
+            ScenarioDef { count: 0, ..def.clone() },
+            ScenarioDef { skills: BTreeMap::new(), ..def.clone() },
+            ScenarioDef { refill_chance: 1.5, ..def.clone() },
+            ScenarioDef { movement: Movement { radius: 0.0, ..def.movement }, ..def.clone() },
+            ScenarioDef { spawn: SpawnRegion::Points { positions: Vec::new() }, ..def.clone() },
+            ScenarioDef { spawn: SpawnRegion::Box { min: Vec3::ONE, max: Vec3::ZERO }, ..def.clone() },
+        ];
+        for def in broken {
+            assert!(def.validate().is_err(), "{:?}", def);
+        }
+    }
+
+    #[test]
+    fn user_files_override_by_name_and_add_scenarios() {
+        let dir = std::env::temp_dir().join(format!("neurocurve_scenarios_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let static_clicking = built_in("static_clicking.toml");
+        // Overrides StaticClicking (names match ignoring case), adds a scenario named after its file, and a broken one
+        let override_toml = static_clicking.replace("\"StaticClicking\"", "\"staticclicking\"").replace("count = 5", "count = 3");
+        std::fs::write(dir.join("a.toml"), override_toml).unwrap();
+        std::fs::write(dir.join("my_drill.toml"), static_clicking.replace("name = \"StaticClicking\"\n", "")).unwrap();
+        std::fs::write(dir.join("broken.toml"), "count = ").unwrap();
+
+        let defs: Vec<ScenarioDef> = definitions::load_dir("scenario", BUILT_IN, &dir, |def| &mut def.name, ScenarioDef::validate);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(defs.len(), BUILT_IN.len() + 1);
+        let position = |name: &str| defs.iter().position(|def| def.name.eq_ignore_ascii_case(name)).unwrap();
+        assert_eq!(position("StaticClicking"), 1, "an override keeps the built-in's place");
+        assert_eq!(defs[1].count, 3);
+        assert_eq!(defs.last().unwrap().name, "my_drill");
+    }
+}
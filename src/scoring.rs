@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use bevy_fps_controller::controller::{LogicalPlayer, RenderPlayer};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{cast_crosshair_ray, fitts::{self, FittsSample}, flick::FlickSegmentation, miss::{self, MissSample}, reaction::{self, TargetReaction},
+            smoothness::Smoothness, ScenarioState, ScenarioType, Target};
+
+const TRACKING_DPS: f32 = 100.0; // Damage per second with the crosshair on a tracking target
+const TRACKING_KILL_DAMAGE: f32 = 100.0; // Damage counted as one kill towards the score
+
+// Results of one scenario run, or of free play when `scenario` is None
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreCard {
+    pub scenario: Option<ScenarioType>,
+    pub hits: u32,
+    pub misses: u32,
+    pub duration: f32,       // Seconds the scenario ran for
+    pub reactions: Vec<TargetReaction>, // Timelines of killed targets, plus survivors once the scenario ends
+    pub flicks: Vec<FlickSegmentation>, // Segmented flicks that ended in a kill
+    pub fitts: Vec<FittsSample>,        // Kills in clicking scenarios
+    pub smoothness: Option<Smoothness>, // Tracking scenarios, once ended
+    pub missed_shots: Vec<MissSample>,  // Misses resolved against the nearest target
+    pub trigger_time: f32,   // Tracking: seconds the trigger was held
+    pub on_target_time: f32, // Tracking: seconds the trigger was held with the crosshair on the target
+    pub damage: f32,
+}
+
+impl ScoreCard {
+    pub fn new(scenario: Option<ScenarioType>) -> Self {
+        Self { scenario, ..default() }
+    }
+
+    pub fn record_hit(&mut self, reaction: Option<TargetReaction>) {
+        self.hits += 1;
+        self.reactions.extend(reaction);
+    }
+
+    pub fn record_miss(&mut self) {
+        self.misses += 1;
+    }
+
+    // One frame of tracking with the trigger held
+    pub fn record_tracking(&mut self, delta: f32, on_target: bool) {
+        self.trigger_time += delta;
+        if on_target {
+            self.on_target_time += delta;
+            self.damage += TRACKING_DPS * delta;
+        }
+    }
+
+    pub fn is_tracking(&self) -> bool {
+        self.scenario.is_some_and(ScenarioType::is_tracking)
+    }
+
+    // Fraction of the scenario spent on target
+    pub fn time_on_target(&self) -> f32 {
+        if self.duration <= 0.0 { 0.0 } else { (self.on_target_time / self.duration).min(1.0) }
+    }
+
+    // Fraction of trigger time spent on target
+    pub fn tracking_accuracy(&self) -> f32 {
+        if self.trigger_time <= 0.0 { 0.0 } else { self.on_target_time / self.trigger_time }
+    }
+
+    pub fn shots_fired(&self) -> u32 {
+        self.hits + self.misses
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired() == 0 { 0.0 } else { self.hits as f32 / self.shots_fired() as f32 }
+    }
+
+    pub fn kills_per_second(&self) -> f32 {
+        if self.duration <= 0.0 { 0.0 } else { self.hits as f32 / self.duration }
+    }
+
+    pub fn mean_time_to_kill(&self) -> Option<f32> {
+        reaction::mean(&self.reactions, TargetReaction::time_to_kill)
+    }
+
+    // Hits minus misses, as the old points counter did; tracking counts damage in kill-sized chunks
+    pub fn score(&self) -> i32 {
+        if self.is_tracking() {
+            return (self.damage / TRACKING_KILL_DAMAGE) as i32;
+        }
+        self.hits as i32 - self.misses as i32
+    }
+
+    pub fn summary(&self) -> String {
+        let name = self.scenario.map_or("Free play".to_string(), |scenario| format!("{:?}", scenario));
+        if self.is_tracking() {
+            return format!("{}: Score {} | On target {:.0}% | Tracking acc {:.0}% | Damage {:.0}",
+                           name, self.score(), self.time_on_target() * 100.0,
+                           self.tracking_accuracy() * 100.0, self.damage);
+        }
+        let ttk = self.mean_time_to_kill().map_or("-".to_string(), |ttk| format!("{:.2}s", ttk));
+        format!("{}: Score {} | Hits {} Misses {} ({} shots) | Acc {:.0}% | {:.2} kills/s | TTK {}",
+                name, self.score(), self.hits, self.misses, self.shots_fired(),
+                self.accuracy() * 100.0, self.kills_per_second(), ttk)
+    }
+
+    // Mean primary endpoint error, corrections and settle time over the scenario's segmented flicks
+    pub fn flick_summary(&self) -> Option<String> {
+        if self.flicks.is_empty() {
+            return None;
+        }
+        let count = self.flicks.len() as f32;
+        let mean = |metric: fn(&FlickSegmentation) -> f32| self.flicks.iter().map(metric).sum::<f32>() / count;
+        let endpoint_error = mean(|flick| flick.endpoint_error);
+        Some(format!("Flicks: {} of {:.1} deg | Primary {:.0}ms, endpoint {:+.1}% ({}) | {:.1} corrections | settle {:.0}ms",
+                     self.flicks.len(), mean(|flick| flick.distance).to_degrees(),
+                     mean(|flick| flick.primary_time) * 1000.0, endpoint_error * 100.0,
+                     if endpoint_error > 0.0 { "overshoot" } else { "undershoot" },
+                     mean(|flick| flick.corrections as f32), mean(|flick| flick.settle_time) * 1000.0))
+    }
+}
+
+// Live card for the running scenario plus the cards of every scenario completed in this sequence
+#[derive(Resource, Default)]
+pub struct SessionResults {
+    pub current: ScoreCard,
+    pub completed: Vec<ScoreCard>,
+}
+
+impl SessionResults {
+    pub fn start_scenario(&mut self, scenario: ScenarioType) {
+        self.current = ScoreCard::new(Some(scenario));
+    }
+
+    // File the running card and go back to free play
+    pub fn finish_scenario(&mut self, duration: f32) {
+        let mut card = std::mem::take(&mut self.current);
+        card.duration = duration;
+        println!("{}", card.summary());
+        if let Some(flicks) = card.flick_summary() {
+            println!("{}", flicks);
+        }
+        if let Some(misses) = miss::summary(&card.missed_shots) {
+            println!("{}", misses);
+        }
+        if let Some(fit) = fitts::fit(&card.fitts) {
+            println!("Fitts: MT = {:.0}ms + {:.0}ms/bit * ID | throughput {:.2} bits/s ({} kills)",
+                     fit.intercept * 1000.0, fit.slope * 1000.0, fit.throughput, fit.samples);
+        }
+        self.completed.push(card);
+    }
+}
+
+// Score tracking scenarios every frame the trigger is held, by whether the crosshair ray is on a target
+pub fn score_tracking(
+    time: Res<Time>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    scenario_state: Res<ScenarioState>,
+    rapier_context: ReadRapierContext,
+    player_query: Query<Entity, With<LogicalPlayer>>,
+    camera: Query<&Transform, With<RenderPlayer>>,
+    targets: Query<(), With<Target>>,
+    mut results: ResMut<SessionResults>,
+) {
+    if !scenario_state.is_active || !results.current.is_tracking() || !buttons.pressed(MouseButton::Left) {
+        return;
+    }
+    let (Ok(player), Ok(camera_transform)) = (player_query.get_single(), camera.get_single()) else { return };
+
+    let on_target = cast_crosshair_ray(&rapier_context, camera_transform, player)
+        .is_some_and(|(entity, _)| targets.contains(entity));
+    results.current.record_tracking(time.delta_secs(), on_target);
+}
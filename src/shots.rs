@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use std::{fs, io::{self, BufWriter, Write}, path::{Path, PathBuf}};
+
+use crate::{columnar::{self, Column}, flick::FlickSample, telemetry::MotionRecorder, ScenarioEvent, ScenarioType};
+
+// One click, resolved against the target it hit or, for a miss, the target nearest the crosshair
+#[derive(Debug, Clone, Copy)]
+pub struct ShotEvent {
+    pub time: f64, // Seconds on the motion telemetry clock, so shots line up with the motion samples
+    pub scenario: Option<ScenarioType>,
+    pub index: u32, // Shot number within the scenario
+    pub hit: bool,
+    pub target: Option<Entity>,
+    pub target_position: Vec3,
+    pub target_velocity: Vec3,
+    pub yaw: f32, // Camera orientation, radians
+    pub pitch: f32,
+    pub error: Vec2, // Target offset from the crosshair, radians (right, up)
+    pub since_spawn: Option<f32>,
+}
+
+// Flicks from every shot this run, and the current scenario's shot events
+#[derive(Resource, Default)]
+pub struct ShotLog {
+    pub flicks: Vec<FlickSample>,
+    pub shots: Vec<ShotEvent>,
+}
+
+fn scenario_name(event: &ShotEvent) -> String {
+    event.scenario.map_or("FreePlay".to_string(), |scenario| format!("{:?}", scenario))
+}
+
+fn write_csv(path: &Path, events: &[ShotEvent]) -> io::Result<()> {
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    writeln!(out, "time,scenario,shot,hit,target,target_x,target_y,target_z,target_vx,target_vy,target_vz,\
+                   yaw,pitch,error_x,error_y,error_deg,since_spawn")?;
+    for e in events {
+        writeln!(out, "{:.6},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}", e.time, scenario_name(e), e.index,
+                 e.hit as u8, e.target.map_or(String::new(), |target| target.to_bits().to_string()),
+                 e.target_position.x, e.target_position.y, e.target_position.z,
+                 e.target_velocity.x, e.target_velocity.y, e.target_velocity.z, e.yaw, e.pitch,
+                 e.error.x, e.error.y, e.error.length().to_degrees(),
+                 e.since_spawn.map_or(String::new(), |since| since.to_string()))?;
+    }
+    out.flush()
+}
+
+// Same columns as the CSV, minus the scenario name which is in the file name
+fn write_columnar(path: &Path, events: &[ShotEvent]) -> io::Result<()> {
+    let f32s = |value: fn(&ShotEvent) -> f32| Column::F32(events.iter().map(value).collect());
+    columnar::write(path, &[
+        ("time", Column::F64(events.iter().map(|e| e.time).collect())),
+        ("shot", Column::U32(events.iter().map(|e| e.index).collect())),
+        ("hit", Column::Bool(events.iter().map(|e| e.hit).collect())),
+        ("target", Column::U64(events.iter().map(|e| e.target.map_or(u64::MAX, Entity::to_bits)).collect())),
+        ("target_x", f32s(|e| e.target_position.x)),
+        ("target_y", f32s(|e| e.target_position.y)),
+        ("target_z", f32s(|e| e.target_position.z)),
+        ("target_vx", f32s(|e| e.target_velocity.x)),
+        ("target_vy", f32s(|e| e.target_velocity.y)),
+        ("target_vz", f32s(|e| e.target_velocity.z)),
+        ("yaw", f32s(|e| e.yaw)),
+        ("pitch", f32s(|e| e.pitch)),
+        ("error_x", f32s(|e| e.error.x)),
+        ("error_y", f32s(|e| e.error.y)),
+        ("since_spawn", f32s(|e| e.since_spawn.unwrap_or(f32::NAN))),
+    ])
+}
+
+// Write a scenario's shots as `<index>_<scenario>_shots.csv` and `.ncol` in the session directory
+pub fn write_scenario(dir: &Path, index: usize, scenario: ScenarioType, events: &[ShotEvent]) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{:02}_{:?}_shots.csv", index, scenario));
+    write_csv(&path, events)?;
+    write_columnar(&path.with_extension("ncol"), events)?;
+    Ok(path)
+}
+
+// Start each scenario with an empty shot log and save it when the scenario ends
+pub fn save_shot_log(mut events: EventReader<ScenarioEvent>, mut shot_log: ResMut<ShotLog>, recorder: Res<MotionRecorder>) {
+    for event in events.read() {
+        match *event {
+            ScenarioEvent::Started => shot_log.shots.clear(),
+            ScenarioEvent::Ended { index, scenario } => {
+                match write_scenario(&recorder.session_dir(), index, scenario, &shot_log.shots) {
+                    Ok(path) => println!("Saved {} shots to {}", shot_log.shots.len(), path.display()),
+                    Err(err) => eprintln!("Failed to save shot log: {}", err),
+                }
+            },
+            ScenarioEvent::Completed => {},
+        }
+    }
+}
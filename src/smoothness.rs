@@ -0,0 +1,270 @@
+use bevy::{prelude::*, tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task}};
+use bevy_fps_controller::controller::{FpsControllerInput, RenderPlayer};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+use crate::{flick, scoring::SessionResults, ScenarioEvent, ScenarioState, Target};
+
+const RESAMPLE_HZ: f32 = 240.0; // Frames are irregular, so the aim path is resampled to a fixed rate first
+const WINDOW_SECONDS: f32 = 1.0; // Jerk and SPARC are computed per window and averaged over the scenario
+const SPARC_CUTOFF_HZ: f32 = 10.0;
+const SPARC_AMPLITUDE_THRESHOLD: f32 = 0.05;
+const SPARC_PADDING: usize = 4;
+const TREMOR_HZ: f32 = 8.0; // Physiological tremor sits around 8-12 Hz, deliberate tracking well below
+
+// Crosshair orientation and target offset for one frame of a tracking scenario
+#[derive(Debug, Clone, Copy)]
+pub struct TrackingSample {
+    pub time: f32,
+    pub aim: Vec2,   // Radians (right, up)
+    pub error: Vec2, // Target offset from the crosshair, radians (right, up)
+}
+
+#[derive(Resource, Default)]
+pub struct TrackingRecorder {
+    samples: Vec<TrackingSample>,
+}
+
+// Metrics still being computed off the main thread, by index of the completed card they belong to
+#[derive(Resource, Default)]
+pub struct PendingSmoothness(Vec<(usize, Task<Option<Smoothness>>)>);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Smoothness {
+    pub normalized_jerk: f32, // Dimensionless, mean over windows; lower is smoother
+    pub sparc: f32,           // Spectral arc length of the speed profile, mean over windows; closer to 0 is smoother
+    pub rms_error: f32,       // Radians between crosshair and target
+    pub tremor_energy: f32,   // Fraction of aim velocity power above TREMOR_HZ
+}
+
+impl Smoothness {
+    pub fn summary(&self) -> String {
+        format!("Smoothness: jerk {:.0} | SPARC {:.2} | RMS error {:.2} deg | tremor {:.1}%",
+                self.normalized_jerk, self.sparc, self.rms_error.to_degrees(), self.tremor_energy * 100.0)
+    }
+}
+
+pub fn analyze(samples: &[TrackingSample]) -> Option<Smoothness> {
+    let dt = 1.0 / RESAMPLE_HZ;
+    let (aim, error) = resample(samples, dt);
+    let window = (WINDOW_SECONDS * RESAMPLE_HZ) as usize;
+    if aim.len() < window {
+        return None;
+    }
+
+    let rms_error = (error.iter().map(|e| e.length_squared()).sum::<f32>() / error.len() as f32).sqrt();
+    let (mut jerks, mut sparcs) = (Vec::new(), Vec::new());
+    for positions in aim.chunks_exact(window) {
+        let velocity = derivative(positions, dt);
+        jerks.extend(normalized_jerk(&velocity, dt));
+        sparcs.extend(sparc(&velocity.iter().map(|v| v.length()).collect::<Vec<_>>()));
+    }
+
+    let mean = |values: &[f32]| if values.is_empty() { 0.0 } else { values.iter().sum::<f32>() / values.len() as f32 };
+    Some(Smoothness {
+        normalized_jerk: mean(&jerks),
+        sparc: mean(&sparcs),
+        rms_error,
+        tremor_energy: tremor_energy(&derivative(&aim, dt)),
+    })
+}
+
+// Share of the velocity power above TREMOR_HZ, over the whole scenario so a slow sweep spans many frequency bins.
+// Hann-windowed so the sweep cut off at either end doesn't leak into the tremor band.
+fn tremor_energy(velocity: &[Vec2]) -> f32 {
+    let hann = |n: usize| 0.5 - 0.5 * (TAU * n as f32 / (velocity.len() - 1).max(1) as f32).cos();
+    let len = velocity.len().next_power_of_two();
+    let (mut tremor_power, mut total_power) = (0.0, 0.0);
+    for axis in [|v: &Vec2| v.x, |v: &Vec2| v.y] {
+        let component: Vec<f32> = velocity.iter().enumerate().map(|(n, v)| axis(v) * hann(n)).collect();
+        for (k, magnitude) in spectrum(&component, len).into_iter().enumerate() {
+            let power = magnitude * magnitude;
+            total_power += power;
+            if k as f32 * RESAMPLE_HZ / len as f32 >= TREMOR_HZ {
+                tremor_power += power;
+            }
+        }
+    }
+    if total_power > 0.0 { tremor_power / total_power } else { 0.0 }
+}
+
+// Linear interpolation of aim and error onto a uniform time grid
+fn resample(samples: &[TrackingSample], dt: f32) -> (Vec<Vec2>, Vec<Vec2>) {
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else { return default() };
+    let count = ((last.time - first.time) / dt) as usize;
+    let mut segment = 0;
+    (0..count).map(|i| {
+        let time = first.time + i as f32 * dt;
+        while segment + 2 < samples.len() && samples[segment + 1].time < time {
+            segment += 1;
+        }
+        let (a, b) = (samples[segment], samples[(segment + 1).min(samples.len() - 1)]);
+        let t = if b.time > a.time { ((time - a.time) / (b.time - a.time)).clamp(0.0, 1.0) } else { 0.0 };
+        (a.aim.lerp(b.aim, t), a.error.lerp(b.error, t))
+    }).unzip()
+}
+
+fn derivative(values: &[Vec2], dt: f32) -> Vec<Vec2> {
+    values.windows(2).map(|pair| (pair[1] - pair[0]) / dt).collect()
+}
+
+// sqrt(1/2 * integral of squared jerk * T^5 / L^2), skipping windows with no movement
+fn normalized_jerk(velocity: &[Vec2], dt: f32) -> Option<f32> {
+    let length = velocity.iter().map(|v| v.length() * dt).sum::<f32>();
+    if velocity.len() < 3 || length < f32::EPSILON {
+        return None;
+    }
+    let jerk = derivative(&derivative(velocity, dt), dt);
+    let integral = jerk.iter().map(|j| j.length_squared() * dt).sum::<f32>();
+    let duration = velocity.len() as f32 * dt;
+    Some((0.5 * integral * duration.powi(5) / (length * length)).sqrt())
+}
+
+// Arc length of the normalised speed spectrum up to the adaptive cutoff (Balasubramanian et al. 2015)
+fn sparc(speed: &[f32]) -> Option<f32> {
+    let padded = (speed.len() * SPARC_PADDING).next_power_of_two();
+    let spectrum = spectrum(speed, padded);
+    let peak = spectrum.iter().copied().fold(0.0, f32::max);
+    if peak <= f32::EPSILON {
+        return None;
+    }
+    let step = RESAMPLE_HZ / padded as f32;
+    let in_band = ((SPARC_CUTOFF_HZ / step) as usize + 1).min(spectrum.len());
+    let cutoff = spectrum[..in_band].iter().rposition(|m| m / peak >= SPARC_AMPLITUDE_THRESHOLD)? + 1;
+    let band = cutoff as f32 * step;
+    Some(-spectrum[..cutoff].windows(2)
+        .map(|pair| ((step / band).powi(2) + ((pair[1] - pair[0]) / peak).powi(2)).sqrt())
+        .sum::<f32>())
+}
+
+// Magnitudes of the first half of the DFT of `signal` zero-padded to `len`, a power of two, by radix-2 FFT
+fn spectrum(signal: &[f32], len: usize) -> Vec<f32> {
+    debug_assert!(len.is_power_of_two() && signal.len() <= len);
+    let bits = len.trailing_zeros();
+    let mut data = vec![(0.0f32, 0.0f32); len];
+    for (n, &x) in signal.iter().enumerate() {
+        data[n.reverse_bits().checked_shr(usize::BITS - bits).unwrap_or(0)] = (x, 0.0);
+    }
+
+    let mut size = 2;
+    while size <= len {
+        let half = size / 2;
+        for start in (0..len).step_by(size) {
+            for k in 0..half {
+                let (sin, cos) = (-TAU * k as f32 / size as f32).sin_cos();
+                let (even, odd) = (data[start + k], data[start + k + half]);
+                let twiddled = (odd.0 * cos - odd.1 * sin, odd.0 * sin + odd.1 * cos);
+                data[start + k] = (even.0 + twiddled.0, even.1 + twiddled.1);
+                data[start + k + half] = (even.0 - twiddled.0, even.1 - twiddled.1);
+            }
+        }
+        size *= 2;
+    }
+    data[..=len / 2].iter().map(|(re, im)| (re * re + im * im).sqrt()).collect()
+}
+
+// Sample aim and target offset every frame of a tracking scenario
+pub fn record_tracking(
+    time: Res<Time>,
+    scenario_state: Res<ScenarioState>,
+    results: Res<SessionResults>,
+    input: Query<&FpsControllerInput>,
+    camera: Query<&Transform, With<RenderPlayer>>,
+    targets: Query<&Transform, With<Target>>,
+    mut recorder: ResMut<TrackingRecorder>,
+) {
+    if !scenario_state.is_active || !results.current.is_tracking() {
+        return;
+    }
+    let (Ok(input), Ok(camera_transform)) = (input.get_single(), camera.get_single()) else { return };
+    let Some(error) = targets.iter()
+        .map(|target| flick::angular_offset(camera_transform, target.translation))
+        .min_by(|a, b| a.length().total_cmp(&b.length())) else { return };
+
+    recorder.samples.push(TrackingSample { time: time.elapsed_secs(), aim: Vec2::new(-input.yaw, input.pitch), error });
+}
+
+// Start each scenario with no samples and analyze tracking scenarios off the main thread as they end, since long
+// scenarios take a while
+pub fn save_smoothness(mut events: EventReader<ScenarioEvent>, mut recorder: ResMut<TrackingRecorder>,
+                       results: Res<SessionResults>, mut pending: ResMut<PendingSmoothness>) {
+    for event in events.read() {
+        match *event {
+            ScenarioEvent::Started => recorder.samples.clear(),
+            ScenarioEvent::Ended { .. } => {
+                if !results.completed.last().is_some_and(|card| card.is_tracking()) {
+                    continue;
+                }
+                let samples = std::mem::take(&mut recorder.samples);
+                let task = AsyncComputeTaskPool::get().spawn(async move { analyze(&samples) });
+                pending.0.push((results.completed.len() - 1, task));
+            },
+            ScenarioEvent::Completed => {},
+        }
+    }
+}
+
+// Attach finished metrics to their cards, waiting for any still running once the sequence completes so the
+// history and results screen see them all
+pub fn collect_smoothness(mut events: EventReader<ScenarioEvent>, mut pending: ResMut<PendingSmoothness>,
+                          mut results: ResMut<SessionResults>) {
+    let completed = events.read().any(|event| matches!(event, ScenarioEvent::Completed));
+    pending.0.retain_mut(|(index, task)| {
+        let smoothness = if completed { Some(block_on(task)) } else { block_on(future::poll_once(task)) };
+        let Some(smoothness) = smoothness else { return true };
+        if let Some(card) = results.completed.get_mut(*index) {
+            card.smoothness = smoothness;
+            if let Some(smoothness) = smoothness {
+                println!("{}", smoothness.summary());
+            }
+        }
+        false
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fft_matches_direct_dft() {
+        let signal: Vec<f32> = (0..100).map(|n| (n as f32 * 0.3).sin() + 0.5 * (n as f32 * 1.7).cos() + 0.1 * n as f32).collect();
+        let len = 128;
+        let fft = spectrum(&signal, len);
+        assert_eq!(fft.len(), len / 2 + 1);
+        for (k, magnitude) in fft.into_iter().enumerate() {
+            let (re, im) = signal.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, &x)| {
+                let angle = -TAU * k as f32 * n as f32 / len as f32;
+                (re + x * angle.cos(), im + x * angle.sin())
+            });
+            let direct = (re * re + im * im).sqrt();
+            assert!((magnitude - direct).abs() < 1e-3 * direct.max(1.0), "bin {}: {} vs {}", k, magnitude, direct);
+        }
+    }
+
+    // 5 s of a 0.5 Hz sweep of 0.2 rad plus a 10 Hz wobble of `wobble` rad
+    fn sweep(wobble: f32) -> Vec<TrackingSample> {
+        (0..1200).map(|i| {
+            let time = i as f32 / 240.0;
+            let aim = Vec2::new(0.2 * (TAU * 0.5 * time).sin() + wobble * (TAU * 10.0 * time).sin(), 0.0);
+            TrackingSample { time, aim, error: Vec2::new(0.01, 0.0) }
+        }).collect()
+    }
+
+    #[test]
+    fn tremor_is_the_power_share_of_the_wobble() {
+        // Velocity amplitudes are 2 pi f times the position amplitudes, so the 10 Hz share of the power is
+        // (0.002 * 10)^2 / ((0.2 * 0.5)^2 + (0.002 * 10)^2) = 0.04 / 1.04
+        let smoothness = analyze(&sweep(0.002)).unwrap();
+        let expected = 0.04 / 1.04;
+        assert!((smoothness.tremor_energy - expected).abs() < 0.02 * expected, "{} vs {}", smoothness.tremor_energy, expected);
+        assert!((smoothness.rms_error - 0.01).abs() < 1e-5);
+        assert!(smoothness.sparc < 0.0);
+    }
+
+    #[test]
+    fn slow_sweep_has_no_tremor() {
+        let smoothness = analyze(&sweep(0.0)).unwrap();
+        assert!(smoothness.tremor_energy < 1e-4, "{}", smoothness.tremor_energy);
+    }
+}
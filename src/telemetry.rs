@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use std::{collections::VecDeque, fs, io::{self, BufWriter, Write}, path::{Path, PathBuf}, time::{Instant, SystemTime, UNIX_EPOCH}};
+
+use crate::{columnar::{self, Column}, profile, ScenarioEvent, ScenarioState, ScenarioType};
+
+const RING_CAPACITY: usize = 1 << 17; // Kept outside scenarios, ~16 s at 8000 Hz
+const MAX_POLLING_HZ: f32 = 8000.0; // Scenarios keep every sample up to this polling rate
+const SCENARIO_SLACK_SECONDS: f32 = 1.0; // Frame timing can run a scenario slightly past its duration
+
+// One mouse motion event and its effect on the crosshair
+#[derive(Debug, Clone, Copy)]
+pub struct MotionSample {
+    pub time: f64, // Seconds since startup; events within a frame are spread evenly across it
+    pub dx: i32,   // Raw counts
+    pub dy: i32,
+    pub yaw_delta: f32, // Radians, positive = right
+    pub pitch_delta: f32, // Radians, positive = up
+    pub yaw: f32,   // Crosshair orientation after the event
+    pub pitch: f32,
+    pub firing: bool,
+    pub aiming_down_sights: bool,
+}
+
+// Ring buffer of motion samples for the current scenario, written to disk as each scenario ends.
+// Sized to hold a whole scenario at MAX_POLLING_HZ; it only grows as samples arrive.
+#[derive(Resource)]
+pub struct MotionRecorder {
+    start: Instant,
+    session: String,
+    samples: VecDeque<MotionSample>,
+    capacity: usize,
+    dropped: usize,
+}
+
+impl Default for MotionRecorder {
+    fn default() -> Self {
+        let session = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()).to_string();
+        Self { start: Instant::now(), session, samples: VecDeque::with_capacity(RING_CAPACITY), capacity: RING_CAPACITY,
+               dropped: 0 }
+    }
+}
+
+impl MotionRecorder {
+    // Seconds since the recorder was created, at full Instant resolution
+    pub fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    pub fn record(&mut self, sample: MotionSample) {
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+            self.dropped += 1;
+        }
+        self.samples.push_back(sample);
+    }
+
+    // Empty the buffer for a scenario of `duration` seconds
+    pub fn start_scenario(&mut self, duration: f32) {
+        self.samples.clear();
+        self.capacity = RING_CAPACITY.max(((duration + SCENARIO_SLACK_SECONDS) * MAX_POLLING_HZ) as usize);
+        self.dropped = 0;
+    }
+
+    // Unix seconds at startup, naming this run's telemetry directory
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    // Keep writing into an earlier run's directory when resuming its session
+    pub fn resume_session(&mut self, session: &str) {
+        self.session = session.to_string();
+    }
+
+    pub fn session_dir(&self) -> PathBuf {
+        profile::data_dir().join("telemetry").join(&self.session)
+    }
+
+    // Write the buffered samples as CSV to `<session>/<index>_<scenario>.csv`, and in columnar form next to it
+    pub fn write_scenario(&self, index: usize, scenario: ScenarioType) -> io::Result<PathBuf> {
+        let dir = self.session_dir();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{:02}_{:?}.csv", index, scenario));
+        write_csv(&path, self.samples.iter())?;
+        write_columnar(&path.with_extension("ncol"), &self.samples)?;
+        Ok(path)
+    }
+}
+
+fn write_csv<'a>(path: &Path, samples: impl Iterator<Item = &'a MotionSample>) -> io::Result<()> {
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    writeln!(out, "time,dx,dy,yaw_delta,pitch_delta,yaw,pitch,firing,ads")?;
+    for s in samples {
+        writeln!(out, "{:.6},{},{},{},{},{},{},{},{}", s.time, s.dx, s.dy, s.yaw_delta, s.pitch_delta,
+                 s.yaw, s.pitch, s.firing as u8, s.aiming_down_sights as u8)?;
+    }
+    out.flush()
+}
+
+fn write_columnar(path: &Path, samples: &VecDeque<MotionSample>) -> io::Result<()> {
+    let f32s = |value: fn(&MotionSample) -> f32| Column::F32(samples.iter().map(value).collect());
+    columnar::write(path, &[
+        ("time", Column::F64(samples.iter().map(|s| s.time).collect())),
+        ("dx", Column::I32(samples.iter().map(|s| s.dx).collect())),
+        ("dy", Column::I32(samples.iter().map(|s| s.dy).collect())),
+        ("yaw_delta", f32s(|s| s.yaw_delta)),
+        ("pitch_delta", f32s(|s| s.pitch_delta)),
+        ("yaw", f32s(|s| s.yaw)),
+        ("pitch", f32s(|s| s.pitch)),
+        ("firing", Column::Bool(samples.iter().map(|s| s.firing).collect())),
+        ("ads", Column::Bool(samples.iter().map(|s| s.aiming_down_sights).collect())),
+    ])
+}
+
+// Start each scenario with an empty buffer sized for its duration and save it when the scenario ends
+pub fn save_scenario_telemetry(mut events: EventReader<ScenarioEvent>, mut recorder: ResMut<MotionRecorder>,
+                               scenario_state: Res<ScenarioState>) {
+    for event in events.read() {
+        match *event {
+            ScenarioEvent::Started => recorder.start_scenario(scenario_state.scenario_timer.duration().as_secs_f32()),
+            ScenarioEvent::Ended { index, scenario } => {
+                if recorder.dropped > 0 {
+                    eprintln!("Mouse polled above {} Hz, oldest {} telemetry samples of the scenario dropped",
+                              MAX_POLLING_HZ, recorder.dropped);
+                }
+                match recorder.write_scenario(index, scenario) {
+                    Ok(path) => println!("Saved {} motion samples to {}", recorder.samples.len(), path.display()),
+                    Err(err) => eprintln!("Failed to save telemetry: {}", err),
+                }
+            },
+            ScenarioEvent::Completed => {},
+        }
+    }
+}